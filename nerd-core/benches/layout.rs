@@ -0,0 +1,79 @@
+//! Layout timings for generated schemas of increasing size.
+//!
+//! Run with `cargo bench --bench layout`.
+
+use nerd_core::layout::{LayoutAlgorithm, LayoutEngine};
+use nerd_core::models::{Column, ColumnPair, Dimensions, Entity, Relationship, RelationshipType, Schema};
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 3] = [100, 300, 1000];
+const RUNS: usize = 5;
+
+/// A warehouse-like schema: tables of 3 to 14 columns, most with a foreign
+/// key to an earlier table and some with a second one.
+fn schema(tables: usize) -> Schema {
+    let mut schema = Schema::new();
+    for i in 0..tables {
+        let name = format!("table_{}", i);
+        let mut columns = vec![Column {
+            name: "id".to_string(),
+            data_type: "BIGINT".to_string(),
+            is_primary_key: true,
+            ..Default::default()
+        }];
+        columns.extend((0..2 + i % 12).map(|c| Column {
+            name: format!("column_{}", c),
+            data_type: "VARCHAR(64)".to_string(),
+            nullable: true,
+            ..Default::default()
+        }));
+
+        let parents = [i / 3, i * 7 / 11].into_iter().filter(|&p| p < i).take(1 + i % 2);
+        for parent in parents {
+            let column = format!("table_{}_id", parent);
+            columns.push(Column {
+                name: column.clone(),
+                data_type: "BIGINT".to_string(),
+                is_foreign_key: true,
+                ..Default::default()
+            });
+            schema.relationships.push(Relationship {
+                name: None,
+                from_table: name.clone(),
+                to_table: format!("table_{}", parent),
+                column_pairs: vec![ColumnPair::new(&column, "id")],
+                relationship_type: RelationshipType::OneToMany,
+                optional: false,
+            });
+        }
+
+        let mut entity = Entity { name: name.clone(), columns, ..Default::default() };
+        entity.dimensions = Dimensions::for_entity(&entity);
+        schema.entities.insert(name, entity);
+    }
+    schema
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn main() {
+    for algorithm in [LayoutAlgorithm::ForceDirected, LayoutAlgorithm::Hierarchical] {
+        let mut engine = LayoutEngine::new(800.0, 600.0);
+        engine.set_algorithm(algorithm);
+
+        for tables in SIZES {
+            let times = (0..RUNS)
+                .map(|_| {
+                    let mut schema = schema(tables);
+                    let start = Instant::now();
+                    engine.layout_entities(&mut schema);
+                    start.elapsed()
+                })
+                .collect();
+            println!("{:?} {:>5} tables: {:>8.1?}", algorithm, tables, median(times));
+        }
+    }
+}
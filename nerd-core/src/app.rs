@@ -1,8 +1,13 @@
+use crate::history::{Change, History};
+use crate::migrations::{self, Migration};
 use crate::models::{Schema, Column, Entity, Position, Dimensions};
-use crate::parser::SqlParser;
-use crate::layout::LayoutEngine;
+use crate::parser::{ParseError, SqlDialect, SqlParser};
+use crate::layout::{LayoutAlgorithm, LayoutEngine, UNITS_PER_COLUMN, UNITS_PER_ROW};
+use crate::render::{Camera, HitMap, Neighborhood, RelationshipNotation, RouteCache};
+use crate::search::{self, SearchMatch};
 use crate::sync::SchemaSync;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppMode {
@@ -10,6 +15,8 @@ pub enum AppMode {
     DiagramView,
     Help,
     EntityCreator,
+    EntityDetail,
+    Search,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -22,6 +29,8 @@ pub struct App {
     pub schema: Schema,
     pub mode: AppMode,
     pub selected_entity: Option<String>,
+    /// Column of the selected entity found by the last search.
+    pub selected_column: Option<String>,
     pub sql_content: String,
     pub vim_mode: VimMode,
     pub cursor_position: usize,
@@ -30,6 +39,77 @@ pub struct App {
     pub entity_creator_buffer: String,
     pub schema_sync: SchemaSync,
     pub last_generated_sql: String,
+    /// Dialect forced on the command line; `None` detects it from the SQL.
+    pub dialect: Option<SqlDialect>,
+    /// Dialect detected from `sql_content` when it was last applied.
+    detected_dialect: SqlDialect,
+    /// `sql_content` when it last matched the schema (applied, synced or
+    /// generated), for putting it back when a sync is undone.
+    applied_sql: String,
+    /// Migration history loaded from disk, in application order.
+    pub migrations: Vec<Migration>,
+    /// How many of `migrations` the current schema includes.
+    pub migration_cursor: usize,
+    /// Statements of `sql_content` that failed to parse on the last apply.
+    pub parse_errors: Vec<ParseError>,
+    pub notation: RelationshipNotation,
+    /// Relationship routes for the current layout.
+    pub route_cache: RouteCache,
+    /// Part of the canvas shown in the diagram view.
+    pub camera: Camera,
+    pub show_minimap: bool,
+    /// What the last frame drew where, for the mouse.
+    pub hit_map: HitMap,
+    /// Relationship clicked on, by index in `schema.relationships`.
+    pub selected_relationship: Option<usize>,
+    /// Hops around the selected entity that are highlighted; 0 turns it off.
+    pub highlight_hops: usize,
+    /// Set while `schema` only holds the tables around one entity.
+    pub focus: Option<Focus>,
+    pub search_query: String,
+    pub search_matches: Vec<SearchMatch>,
+    /// Match chosen with the arrow keys, by index in `search_matches`.
+    pub search_cursor: usize,
+    /// Where each jump (search, following a foreign key) came from, for
+    /// going back.
+    pub jump_history: Vec<(String, Option<String>)>,
+    /// Edits made in the diagram view, for undo and redo.
+    pub history: History,
+    pub show_history: bool,
+    drag: Option<Drag>,
+}
+
+/// A focused view. The full schema and camera are put back on Esc, so
+/// nothing done to the focused layout leaks into the diagram.
+#[derive(Debug)]
+pub struct Focus {
+    pub center: String,
+    pub hops: usize,
+    /// Relationships from each boundary table to tables outside the view
+    pub stubs: HashMap<String, usize>,
+    full: Schema,
+    camera: Camera,
+}
+
+/// A left-button drag in progress, with the last cell the mouse was on.
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    /// Moving the selected entity, which started at `from`
+    Entity { column: u16, row: u16, from: Position },
+    View { column: u16, row: u16 },
+}
+
+fn entity_center(entity: &Entity) -> (f64, f64) {
+    (
+        entity.position.x + entity.dimensions.width as f64 * UNITS_PER_COLUMN / 2.0,
+        entity.position.y + entity.dimensions.height as f64 * UNITS_PER_ROW / 2.0,
+    )
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
@@ -81,6 +161,7 @@ CREATE TABLE order_items (
             schema: Schema::new(),
             mode: AppMode::DiagramView,
             selected_entity: None,
+            selected_column: None,
             sql_content: sample_sql,
             vim_mode: VimMode::Normal,
             cursor_position: 0,
@@ -89,6 +170,27 @@ CREATE TABLE order_items (
             entity_creator_buffer: String::new(),
             schema_sync: SchemaSync::new(),
             last_generated_sql: String::new(),
+            dialect: None,
+            detected_dialect: SqlDialect::default(),
+            applied_sql: String::new(),
+            migrations: Vec::new(),
+            migration_cursor: 0,
+            parse_errors: Vec::new(),
+            notation: RelationshipNotation::default(),
+            route_cache: RouteCache::new(),
+            camera: Camera::new(),
+            show_minimap: false,
+            hit_map: HitMap::new(),
+            selected_relationship: None,
+            highlight_hops: 1,
+            focus: None,
+            search_query: String::new(),
+            search_matches: Vec::new(),
+            search_cursor: 0,
+            jump_history: Vec::new(),
+            history: History::new(),
+            show_history: false,
+            drag: None,
         };
 
         // Parse the sample SQL and create the initial diagram
@@ -102,10 +204,17 @@ CREATE TABLE order_items (
             AppMode::SqlEditor => self.handle_sql_editor_key(key),
             AppMode::Help => self.handle_help_key(key),
             AppMode::EntityCreator => self.handle_entity_creator_key(key),
+            AppMode::EntityDetail => self.handle_entity_detail_key(key),
+            AppMode::Search => self.handle_search_key(key),
         }
     }
 
     fn handle_diagram_key(&mut self, key: KeyEvent) {
+        // Edits belong to the full schema, so they leave the focus first
+        if self.focus.is_some() && Self::edits_schema(&key) {
+            self.unfocus();
+        }
+
         match key.code {
             KeyCode::Char('q') => self.should_quit = true,
             KeyCode::Char('s') => {
@@ -117,22 +226,65 @@ CREATE TABLE order_items (
             KeyCode::Char('?') => self.mode = AppMode::Help,
             KeyCode::Char('n') => self.mode = AppMode::EntityCreator,
             KeyCode::Char('i') => self.import_sql(),
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
             KeyCode::Char('r') => self.refresh_layout(),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('U') => self.show_history = !self.show_history,
             KeyCode::Char('g') => self.generate_sql_from_schema(),
             KeyCode::Char('v') => self.validate_schema(),
+            KeyCode::Char('t') => self.toggle_notation(),
+            KeyCode::Char('a') => self.toggle_layout_algorithm(),
+            KeyCode::Char('e') => self.show_entity_detail(),
+            KeyCode::Char('p') => self.toggle_pin_selected_entity(),
+            KeyCode::Char('[') => self.step_migration_backward(),
+            KeyCode::Char(']') => self.step_migration_forward(),
+            KeyCode::Char('H') => self.select_entity_towards(-1.0, 0.0),
+            KeyCode::Char('J') => self.select_entity_towards(0.0, 1.0),
+            KeyCode::Char('K') => self.select_entity_towards(0.0, -1.0),
+            KeyCode::Char('L') => self.select_entity_towards(1.0, 0.0),
+            KeyCode::Char('.') => self.cycle_selected_column(1),
+            KeyCode::Char(',') => self.cycle_selected_column(-1),
+            KeyCode::Enter => self.follow_foreign_key(),
+            KeyCode::Backspace => self.jump_back(),
             KeyCode::Tab => self.cycle_selected_entity(),
             KeyCode::BackTab => self.cycle_selected_entity_reverse(),
             KeyCode::Delete | KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.delete_selected_entity();
             }
-            KeyCode::Up => self.move_selected_entity(0, -10),
-            KeyCode::Down => self.move_selected_entity(0, 10),
-            KeyCode::Left => self.move_selected_entity(-10, 0),
-            KeyCode::Right => self.move_selected_entity(10, 0),
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => self.nudge_selected_entity(0.0, -10.0),
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => self.nudge_selected_entity(0.0, 10.0),
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => self.nudge_selected_entity(-10.0, 0.0),
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => self.nudge_selected_entity(10.0, 0.0),
+            KeyCode::Char('k') | KeyCode::Up => self.camera.pan(0, -1),
+            KeyCode::Char('j') | KeyCode::Down => self.camera.pan(0, 1),
+            KeyCode::Char('h') | KeyCode::Left => self.camera.pan(-1, 0),
+            KeyCode::Char('l') | KeyCode::Right => self.camera.pan(1, 0),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.camera.zoom_in(),
+            KeyCode::Char('-') => self.camera.zoom_out(),
+            KeyCode::Char('f') => self.camera.fit(&self.schema),
+            KeyCode::Char('c') => self.center_on_selected_entity(),
+            KeyCode::Char('m') => self.show_minimap = !self.show_minimap,
+            KeyCode::Char('>') => self.highlight_hops += 1,
+            KeyCode::Char('<') => self.highlight_hops = self.highlight_hops.saturating_sub(1),
+            KeyCode::Char('o') => self.focus_on_selected_entity(),
+            KeyCode::Char('/') => {
+                self.search_query.clear();
+                self.update_search();
+                self.mode = AppMode::Search;
+            }
+            KeyCode::Esc => self.unfocus(),
             _ => {}
         }
     }
 
+    fn edits_schema(key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('s' | 'n' | 'i' | 'g' | 'p' | 'u' | '[' | ']') => true,
+            KeyCode::Char('d' | 'r') | KeyCode::Delete => key.modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        }
+    }
+
     fn handle_sql_editor_key(&mut self, key: KeyEvent) {
         match self.vim_mode {
             VimMode::Normal => self.handle_vim_normal_mode(key),
@@ -262,6 +414,153 @@ CREATE TABLE order_items (
         }
     }
 
+    fn handle_entity_detail_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('e') => self.mode = AppMode::DiagramView,
+            _ => {}
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.mode = AppMode::DiagramView,
+            KeyCode::Enter => {
+                if let Some(found) = self.search_matches.get(self.search_cursor).cloned() {
+                    self.jump_to(found.entity, found.column);
+                }
+                self.mode = AppMode::DiagramView;
+            }
+            KeyCode::Up => self.search_cursor = self.search_cursor.saturating_sub(1),
+            KeyCode::Down => {
+                self.search_cursor = (self.search_cursor + 1).min(self.search_matches.len().saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.update_search();
+            }
+            KeyCode::Char(c) => {
+                self.search_query.push(c);
+                self.update_search();
+            }
+            _ => {}
+        }
+    }
+
+    /// Search the full schema, even while focused.
+    fn update_search(&mut self) {
+        let schema = self.focus.as_ref().map_or(&self.schema, |focus| &focus.full);
+        self.search_matches = search::search(schema, &self.search_query);
+        self.search_cursor = 0;
+    }
+
+    /// Select a table (and column) and center the view on it, leaving the
+    /// focus if the table is outside it. Backspace comes back here.
+    fn jump_to(&mut self, entity: String, column: Option<String>) {
+        if let Some(current) = self.selected_entity.take() {
+            self.jump_history.push((current, self.selected_column.take()));
+        }
+        self.show_selection(entity, column);
+    }
+
+    fn jump_back(&mut self) {
+        if let Some((entity, column)) = self.jump_history.pop() {
+            self.show_selection(entity, column);
+        }
+    }
+
+    fn show_selection(&mut self, entity: String, column: Option<String>) {
+        if !self.schema.entities.contains_key(&entity) {
+            self.unfocus();
+        }
+        if !self.schema.entities.contains_key(&entity) {
+            return;
+        }
+        self.selected_entity = Some(entity);
+        self.selected_column = column;
+        self.selected_relationship = None;
+        self.center_on_selected_entity();
+    }
+
+    /// Select the closest entity whose centre lies in the direction
+    /// `(dx, dy)` of the selected one's, preferring those straight ahead
+    /// over those off to the side.
+    fn select_entity_towards(&mut self, dx: f64, dy: f64) {
+        let Some(current) = self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)) else {
+            self.cycle_selected_entity();
+            return;
+        };
+        let (x, y) = entity_center(current);
+
+        let nearest = self
+            .schema
+            .entities
+            .values()
+            .filter(|entity| entity.name != current.name)
+            .filter_map(|entity| {
+                let (ex, ey) = entity_center(entity);
+                let ahead = (ex - x) * dx + (ey - y) * dy;
+                let aside = ((ex - x) * dy - (ey - y) * dx).abs();
+                (ahead > 0.0).then_some((ahead + 2.0 * aside, entity.name.clone()))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, name)) = nearest {
+            self.selected_entity = Some(name);
+            self.selected_column = None;
+            self.selected_relationship = None;
+            if self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)).is_some_and(|e| !self.camera.shows(e)) {
+                self.center_on_selected_entity();
+            }
+        }
+    }
+
+    /// Move the column cursor of the selected entity by `step`, wrapping.
+    fn cycle_selected_column(&mut self, step: isize) {
+        let Some(entity) = self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)) else {
+            return;
+        };
+        let count = entity.columns.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let next = match self.selected_column.as_ref().and_then(|c| entity.columns.iter().position(|col| &col.name == c)) {
+            Some(index) => (index as isize + step).rem_euclid(count),
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        self.selected_column = Some(entity.columns[next as usize].name.clone());
+    }
+
+    /// Jump to the table the selected column references, or the first
+    /// foreign key of the entity when no such column is selected.
+    fn follow_foreign_key(&mut self) {
+        let Some(entity) = self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)) else {
+            return;
+        };
+        let column = self
+            .selected_column
+            .as_ref()
+            .and_then(|name| entity.columns.iter().find(|col| &col.name == name))
+            .filter(|col| col.references.is_some())
+            .or_else(|| entity.columns.iter().find(|col| col.references.is_some()));
+        let Some(column) = column else {
+            return;
+        };
+        let Some(reference) = &column.references else {
+            return;
+        };
+        let to_column = reference
+            .column_pairs
+            .iter()
+            .find(|pair| pair.from_column == column.name)
+            .map(|pair| pair.to_column.clone());
+        let (table, from_column) = (reference.table.clone(), column.name.clone());
+
+        // Coming back lands on the column that was followed
+        self.selected_column = Some(from_column);
+        self.jump_to(table, to_column);
+    }
+
     fn handle_entity_creator_key(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Esc => {
@@ -289,6 +588,7 @@ CREATE TABLE order_items (
             return;
         }
 
+        self.selected_column = None;
         self.selected_entity = match &self.selected_entity {
             None => Some(entities[0].clone()),
             Some(current) => {
@@ -308,6 +608,7 @@ CREATE TABLE order_items (
             return;
         }
 
+        self.selected_column = None;
         self.selected_entity = match &self.selected_entity {
             None => Some(entities.last().unwrap().clone()),
             Some(current) => {
@@ -321,28 +622,246 @@ CREATE TABLE order_items (
         };
     }
 
-    fn move_selected_entity(&mut self, dx: i32, dy: i32) {
-        if let Some(entity_name) = &self.selected_entity.clone() {
-            if let Some(entity) = self.schema.entities.get_mut(entity_name) {
-                entity.position.x = (entity.position.x + dx as f64).max(0.0).min(800.0);
-                entity.position.y = (entity.position.y + dy as f64).max(0.0).min(600.0);
+    fn show_entity_detail(&mut self) {
+        if let Some(entity_name) = &self.selected_entity
+            && self.schema.entities.contains_key(entity_name)
+        {
+            self.mode = AppMode::EntityDetail;
+        }
+    }
+
+    fn move_selected_entity(&mut self, dx: f64, dy: f64) {
+        if let Some(entity_name) = &self.selected_entity.clone()
+            && let Some(entity) = self.schema.entities.get_mut(entity_name)
+        {
+            entity.position.x = (entity.position.x + dx).max(0.0);
+            entity.position.y = (entity.position.y + dy).max(0.0);
+        }
+    }
+
+    /// Click selects the entity or relationship under the pointer, dragging
+    /// moves the entity (or the view, from empty space), and the wheel
+    /// zooms, or pans with Shift held.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.mode != AppMode::DiagramView {
+            return;
+        }
+        let (column, row) = (mouse.column, mouse.row);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(name) = self.hit_map.entity_at(column, row) {
+                    self.selected_entity = Some(name.to_string());
+                    self.selected_column = None;
+                    self.selected_relationship = None;
+                    let from = self.schema.entities[name].position;
+                    self.drag = Some(Drag::Entity { column, row, from });
+                } else if let Some(index) = self.hit_map.relationship_at(column, row) {
+                    self.selected_relationship = Some(index);
+                    self.drag = None;
+                } else {
+                    self.selected_relationship = None;
+                    self.drag = Some(Drag::View { column, row });
+                }
             }
+            MouseEventKind::Drag(MouseButton::Left) => match self.drag {
+                Some(Drag::Entity { column: last_column, row: last_row, from }) => {
+                    let (dx, dy) = self.camera.cells_to_units(
+                        column as f64 - last_column as f64,
+                        row as f64 - last_row as f64,
+                    );
+                    self.move_selected_entity(dx, dy);
+                    self.drag = Some(Drag::Entity { column, row, from });
+                }
+                Some(Drag::View { column: last_column, row: last_row }) => {
+                    let (dx, dy) = self.camera.cells_to_units(
+                        column as f64 - last_column as f64,
+                        row as f64 - last_row as f64,
+                    );
+                    self.camera.x -= dx;
+                    self.camera.y -= dy;
+                    self.drag = Some(Drag::View { column, row });
+                }
+                None => {}
+            },
+            MouseEventKind::Up(MouseButton::Left) => {
+                // A whole drag is undone in one step
+                if let Some(Drag::Entity { from, .. }) = self.drag.take() {
+                    self.record_move(from);
+                }
+            }
+            MouseEventKind::ScrollUp if mouse.modifiers.contains(KeyModifiers::SHIFT) => self.camera.pan(0, -1),
+            MouseEventKind::ScrollDown if mouse.modifiers.contains(KeyModifiers::SHIFT) => self.camera.pan(0, 1),
+            MouseEventKind::ScrollUp => self.camera.zoom_in(),
+            MouseEventKind::ScrollDown => self.camera.zoom_out(),
+            MouseEventKind::ScrollLeft => self.camera.pan(-1, 0),
+            MouseEventKind::ScrollRight => self.camera.pan(1, 0),
+            _ => {}
+        }
+    }
+
+    fn center_on_selected_entity(&mut self) {
+        if let Some(entity) = self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)) {
+            self.camera.center_on(entity);
+        }
+    }
+
+    /// Show only the tables within `highlight_hops` relationships of the
+    /// selected entity (at least one), laid out on their own. Focusing
+    /// again while focused starts over from the full schema.
+    fn focus_on_selected_entity(&mut self) {
+        let Some(center) = self.selected_entity.clone() else {
+            return;
+        };
+        let hops = self.highlight_hops.max(1);
+        let (full, camera) = match self.focus.take() {
+            Some(focus) => (focus.full, focus.camera),
+            None => (self.schema.clone(), self.camera),
+        };
+        let Some(neighborhood) = Neighborhood::around(&full, &center, hops) else {
+            self.focus = None;
+            self.schema = full;
+            self.camera = camera;
+            return;
+        };
+
+        let inside = |name: &str| neighborhood.entities.contains_key(name);
+        let mut stubs: HashMap<String, usize> = HashMap::new();
+        for relationship in &full.relationships {
+            match (inside(&relationship.from_table), inside(&relationship.to_table)) {
+                (true, false) => *stubs.entry(relationship.from_table.clone()).or_default() += 1,
+                (false, true) => *stubs.entry(relationship.to_table.clone()).or_default() += 1,
+                _ => {}
+            }
+        }
+
+        // Pins hold positions in the full diagram, which mean nothing here
+        let mut schema = full.subset(inside);
+        let pinned: Vec<String> = schema.entities.values().filter(|e| e.pinned).map(|e| e.name.clone()).collect();
+        for entity in schema.entities.values_mut() {
+            entity.pinned = false;
+        }
+        self.layout_engine.layout_entities(&mut schema);
+        for name in &pinned {
+            schema.entities[name].pinned = true;
+        }
+
+        self.schema = schema;
+        self.selected_relationship = None;
+        self.camera.fit(&self.schema);
+        self.focus = Some(Focus { center, hops, stubs, full, camera });
+    }
+
+    /// Back to the full diagram, as it was before focusing.
+    fn unfocus(&mut self) {
+        if let Some(focus) = self.focus.take() {
+            self.schema = focus.full;
+            self.camera = focus.camera;
+            self.selected_relationship = None;
+            if self.selected_entity.as_ref().is_some_and(|name| !self.schema.entities.contains_key(name)) {
+                self.selected_entity = None;
+            }
+        }
+    }
+
+    fn toggle_pin_selected_entity(&mut self) {
+        if let Some(entity_name) = &self.selected_entity
+            && let Some(entity) = self.schema.entities.get_mut(entity_name)
+        {
+            entity.pinned = !entity.pinned;
+            let label = format!("{} {}", if entity.pinned { "Pin" } else { "Unpin" }, entity_name);
+            self.record(label, Change::Pin { entity: entity_name.clone() });
         }
     }
 
     fn delete_selected_entity(&mut self) {
         if let Some(entity_name) = &self.selected_entity.clone() {
-            self.schema.entities.remove(entity_name);
+            let before = self.schema.clone();
+            self.schema.entities.shift_remove(entity_name);
             self.schema.relationships.retain(|r| {
                 r.from_table != *entity_name && r.to_table != *entity_name
             });
             self.selected_entity = None;
+            self.selected_column = None;
+            self.selected_relationship = None;
+            self.record_schema(format!("Delete {}", entity_name), before, self.sql_content.clone());
+        }
+    }
+
+    /// Remember an edit for undo. Edits made while focused are not kept:
+    /// leaving the focus throws them away anyway.
+    fn record(&mut self, label: impl Into<String>, change: Change) {
+        if self.focus.is_none() {
+            self.history.record(label, change);
+        }
+    }
+
+    /// Record a change to the whole schema made since `before`, when the
+    /// editor held `sql_before`.
+    fn record_schema(&mut self, label: impl Into<String>, before: Schema, sql_before: String) {
+        let change = Change::Schema {
+            before: Box::new(before),
+            after: Box::new(self.schema.clone()),
+            sql_before,
+            sql_after: self.sql_content.clone(),
+        };
+        self.record(label, change);
+    }
+
+    /// Record the move of the selected entity from `from` to where it is now.
+    fn record_move(&mut self, from: Position) {
+        if let Some(name) = self.selected_entity.clone()
+            && let Some(entity) = self.schema.entities.get(&name)
+        {
+            let to = entity.position;
+            if to != from {
+                self.record(format!("Move {}", name), Change::Move { entity: name, from, to });
+            }
+        }
+    }
+
+    fn nudge_selected_entity(&mut self, dx: f64, dy: f64) {
+        let Some(from) = self.selected_entity.as_ref().and_then(|name| self.schema.entities.get(name)).map(|e| e.position) else {
+            return;
+        };
+        self.move_selected_entity(dx, dy);
+        self.record_move(from);
+    }
+
+    fn undo(&mut self) {
+        if self.history.undo(&mut self.schema, &mut self.sql_content).is_some() {
+            self.after_history_step();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.history.redo(&mut self.schema, &mut self.sql_content).is_some() {
+            self.after_history_step();
+        }
+    }
+
+    /// Drop selections that the undone or redone edit took away, and keep
+    /// the cursor inside the editor text it may have put back.
+    fn after_history_step(&mut self) {
+        self.applied_sql = self.sql_content.clone();
+        self.detected_dialect = SqlDialect::detect(&self.sql_content);
+        self.cursor_position = self.cursor_position.min(self.sql_content.len());
+        while !self.sql_content.is_char_boundary(self.cursor_position) {
+            self.cursor_position -= 1;
+        }
+        self.selected_relationship = None;
+        if self.selected_entity.as_ref().is_some_and(|name| !self.schema.entities.contains_key(name)) {
+            self.selected_entity = None;
+            self.selected_column = None;
         }
     }
 
     fn import_sql(&mut self) {
         if !self.sql_content.is_empty() {
+            let before = self.schema.clone();
+            let sql_before = self.applied_sql.clone();
             self.parse_and_apply_sql();
+            self.record_schema("Import SQL", before, sql_before);
         } else {
             self.mode = AppMode::SqlEditor;
             self.vim_mode = VimMode::Normal;
@@ -351,27 +870,116 @@ CREATE TABLE order_items (
     }
 
     fn parse_and_apply_sql(&mut self) {
-        let parser = SqlParser::new();
-        if let Ok(mut new_schema) = parser.parse_sql(&self.sql_content) {
-            self.layout_engine.layout_entities(&mut new_schema);
-            self.schema = new_schema;
+        self.apply_sql_as(SqlDialect::detect(&self.sql_content));
+    }
+
+    /// Parse the editor contents as if `detected` had been detected from
+    /// them; a forced dialect still wins.
+    fn apply_sql_as(&mut self, detected: SqlDialect) {
+        self.applied_sql = self.sql_content.clone();
+        self.detected_dialect = detected;
+        let parser = SqlParser::new(self.active_dialect());
+        // Tables from the statements that did parse are still shown
+        let (mut new_schema, errors) = parser.parse_sql_lenient(&self.sql_content);
+        // Pinned tables that survive the re-parse stay where they were
+        for (name, entity) in new_schema.entities.iter_mut() {
+            if let Some(current) = self.schema.entities.get(name).filter(|e| e.pinned) {
+                entity.position = current.position;
+                entity.pinned = true;
+            }
+        }
+        self.layout_engine.layout_entities(&mut new_schema);
+        self.schema = new_schema;
+        self.selected_relationship = None;
+        if errors.is_empty() {
             self.mode = AppMode::DiagramView;
         }
+        self.parse_errors = errors;
     }
 
     fn sync_sql_changes(&mut self) {
-        if let Ok(has_changes) = self.schema_sync.merge_sql_changes(&mut self.schema, &self.sql_content) {
-            if has_changes {
-                self.layout_engine.layout_entities(&mut self.schema);
+        let sql_before = std::mem::replace(&mut self.applied_sql, self.sql_content.clone());
+        self.detected_dialect = SqlDialect::detect(&self.sql_content);
+        let dialect = self.active_dialect();
+        let before = self.schema.clone();
+        let placed: HashSet<String> = self.schema.entities.keys().cloned().collect();
+        let (has_changes, errors) = self.schema_sync.merge_sql_changes(&mut self.schema, &self.sql_content, dialect);
+        if has_changes {
+            // Only the new tables are placed; the rest of the diagram stays put
+            self.layout_engine.layout_new_entities(&mut self.schema, &placed);
+            self.selected_relationship = None;
+            self.record_schema("Sync SQL", before, sql_before);
+        }
+        // Stay in the editor on the first error so it can be fixed
+        match errors.first() {
+            Some(error) => {
+                self.cursor_position = self.get_position_from_line_col(error.line - 1, error.column - 1);
             }
-            // Always switch back to DiagramView after sync attempt
-            self.mode = AppMode::DiagramView;
+            None => self.mode = AppMode::DiagramView,
+        }
+        self.parse_errors = errors;
+    }
+
+    /// Force a dialect instead of detecting it, and re-parse the editor contents.
+    pub fn set_dialect(&mut self, dialect: SqlDialect) {
+        self.dialect = Some(dialect);
+        self.parse_and_apply_sql();
+    }
+
+    /// The dialect used to parse the editor contents. Detection scans the
+    /// whole text, so it is only redone when the contents are applied.
+    pub fn active_dialect(&self) -> SqlDialect {
+        self.dialect.unwrap_or(self.detected_dialect)
+    }
+
+    /// Replace the editor contents with a migration history and show the
+    /// schema after the last migration.
+    pub fn load_migrations(&mut self, migrations: Vec<Migration>) {
+        self.migration_cursor = migrations.len();
+        self.migrations = migrations;
+        self.show_migration(self.migration_cursor);
+    }
+
+    fn step_migration_backward(&mut self) {
+        if self.migration_cursor > 0 {
+            self.show_migration(self.migration_cursor - 1);
+        }
+    }
+
+    fn step_migration_forward(&mut self) {
+        if self.migration_cursor < self.migrations.len() {
+            self.show_migration(self.migration_cursor + 1);
+        }
+    }
+
+    /// Stepping through migrations replaces the whole schema, so earlier
+    /// edits can no longer be undone on top of it.
+    fn show_migration(&mut self, count: usize) {
+        self.history.clear();
+        self.migration_cursor = count;
+        self.sql_content = migrations::script(&self.migrations, count);
+        self.cursor_position = 0;
+        // Every prefix of the history is read with the dialect of the whole
+        let detected = SqlDialect::detect(&migrations::script(&self.migrations, self.migrations.len()));
+        self.apply_sql_as(detected);
+    }
+
+    /// Where the current schema came from, for the status bar.
+    pub fn source_label(&self) -> String {
+        if self.migrations.is_empty() {
+            return "Sample E-commerce Schema Loaded".to_string();
         }
+        let current = match self.migration_cursor {
+            0 => "(empty)",
+            n => self.migrations[n - 1].name.as_str(),
+        };
+        format!("Migration {}/{}: {} | [/] step", self.migration_cursor, self.migrations.len(), current)
     }
 
     fn generate_sql_from_schema(&mut self) {
-        self.sql_content = self.schema_sync.generate_sql(&self.schema);
+        self.sql_content = self.schema_sync.generate_sql(&self.schema, self.active_dialect());
         self.last_generated_sql = self.sql_content.clone();
+        self.applied_sql = self.sql_content.clone();
         self.mode = AppMode::SqlEditor;
         self.vim_mode = VimMode::Normal;
         self.cursor_position = 0; // Start at beginning of generated content
@@ -383,14 +991,40 @@ CREATE TABLE order_items (
         self.refresh_layout();
     }
 
+    fn toggle_notation(&mut self) {
+        self.notation = match self.notation {
+            RelationshipNotation::CrowsFoot => RelationshipNotation::Labels,
+            RelationshipNotation::Labels => RelationshipNotation::CrowsFoot,
+        };
+    }
+
+    fn toggle_layout_algorithm(&mut self) {
+        let algorithm = match self.layout_engine.algorithm() {
+            LayoutAlgorithm::ForceDirected => LayoutAlgorithm::Hierarchical,
+            LayoutAlgorithm::Hierarchical => LayoutAlgorithm::ForceDirected,
+        };
+        self.layout_engine.set_algorithm(algorithm);
+        self.refresh_layout();
+    }
+
+    pub fn set_layout_seed(&mut self, seed: u64) {
+        self.layout_engine.set_seed(seed);
+        self.layout_engine.layout_entities(&mut self.schema);
+    }
+
     fn refresh_layout(&mut self) {
+        let before = Change::positions(&self.schema);
         self.layout_engine.layout_entities(&mut self.schema);
+        let after = Change::positions(&self.schema);
+        if before != after {
+            self.record("Re-layout", Change::Layout { before, after });
+        }
     }
 
     fn create_entity_from_buffer(&mut self) {
         if !self.entity_creator_buffer.trim().is_empty() {
             let entity_name = self.entity_creator_buffer.trim().to_string();
-            let new_entity = Entity {
+            let mut new_entity = Entity {
                 name: entity_name.clone(),
                 columns: vec![
                     Column {
@@ -400,17 +1034,22 @@ CREATE TABLE order_items (
                         is_primary_key: true,
                         is_foreign_key: false,
                         references: None,
+                        ..Default::default()
                     }
                 ],
                 position: Position {
                     x: 400.0 + (self.schema.entities.len() as f64 * 50.0),
                     y: 300.0 + (self.schema.entities.len() as f64 * 30.0),
                 },
-                dimensions: Dimensions { width: 20, height: 8 },
+                ..Default::default()
             };
+            new_entity.dimensions = Dimensions::for_entity(&new_entity);
             
+            let before = self.schema.clone();
             self.schema.entities.insert(entity_name.clone(), new_entity);
+            self.record_schema(format!("Create {}", entity_name), before, self.sql_content.clone());
             self.selected_entity = Some(entity_name);
+            self.selected_column = None;
         }
     }
 
@@ -648,4 +1287,282 @@ mod tests {
         assert_eq!(app.sql_content, "Helo");
         assert_eq!(app.cursor_position, 2); // Cursor stays at same position
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_generated_sql_keeps_sample_defaults() {
+        let mut app = App::new();
+        app.generate_sql_from_schema();
+
+        assert!(app.sql_content.contains("created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP"));
+        assert!(app.sql_content.contains("status VARCHAR(50) DEFAULT 'pending'"));
+        assert!(app.sql_content.contains("stock_quantity INT DEFAULT 0"));
+    }
+
+    #[test]
+    fn test_step_through_migrations() {
+        let dir = std::env::temp_dir().join(format!("nerd-app-migrations-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("V1__users.sql"), "CREATE TABLE users (id INT PRIMARY KEY);").unwrap();
+        std::fs::write(dir.join("V2__posts.sql"), "CREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));").unwrap();
+        std::fs::write(dir.join("V3__drop_posts.sql"), "DROP TABLE posts;\nCREATE SEQUENCE ids START 1 AUTOINCREMENT;").unwrap();
+        let history = migrations::load_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut app = App::new();
+        app.load_migrations(history);
+        assert_eq!(app.migration_cursor, 3);
+        assert_eq!(app.schema.entities.len(), 1);
+        // The history's dialect is detected, not forced
+        assert_eq!(app.dialect, None);
+        assert_eq!(app.active_dialect(), SqlDialect::Snowflake);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('[')));
+        assert_eq!(app.active_dialect(), SqlDialect::Snowflake);
+        assert_eq!(app.schema.entities.len(), 2);
+        assert_eq!(app.schema.relationships.len(), 1);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('[')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('[')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('[')));
+        assert_eq!(app.migration_cursor, 0);
+        assert!(app.schema.entities.is_empty());
+
+        app.handle_key(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(app.source_label(), "Migration 1/3: V1__users.sql | [/] step");
+        assert!(app.schema.entities.contains_key("users"));
+    }
+
+    #[test]
+    fn test_sync_with_parse_error_stays_in_editor() {
+        let mut app = App::new();
+        app.mode = AppMode::SqlEditor;
+        app.sql_content = "CREATE TABLE users (id INT PRIMARY KEY);\nCREATE TABLE posts (id INT,, title TEXT);".to_string();
+
+        app.sync_sql_changes();
+        assert_eq!(app.mode, AppMode::SqlEditor);
+        assert_eq!(app.parse_errors.len(), 1);
+        assert_eq!(app.parse_errors[0].line, 2);
+        assert_eq!(app.get_line_and_column().0, 1);
+        assert_eq!(app.schema.entities.len(), 1);
+        assert!(app.schema.entities.contains_key("users"));
+
+        app.sql_content = "CREATE TABLE users (id INT PRIMARY KEY);".to_string();
+        app.sync_sql_changes();
+        assert!(app.parse_errors.is_empty());
+        assert_eq!(app.mode, AppMode::DiagramView);
+    }
+
+    #[test]
+    fn test_detected_dialect_is_kept_until_the_sql_is_applied() {
+        let mut app = App::new();
+        let detected = app.active_dialect();
+        app.mode = AppMode::SqlEditor;
+        app.sql_content = "CREATE TABLE `users` (id INT AUTO_INCREMENT PRIMARY KEY);".to_string();
+        assert_eq!(app.active_dialect(), detected);
+
+        app.sync_sql_changes();
+        assert_eq!(app.active_dialect(), SqlDialect::MySql);
+    }
+
+    #[test]
+    fn test_pinned_and_existing_entities_stay_put() {
+        let mut app = App::new();
+        app.selected_entity = Some("orders".to_string());
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT));
+        app.handle_key(KeyEvent::from(KeyCode::Char('p')));
+        assert!(app.schema.entities["orders"].pinned);
+
+        let pinned = app.schema.entities["orders"].position;
+        app.handle_key(KeyEvent::from(KeyCode::Char('r')));
+        let after = app.schema.entities["orders"].position;
+        assert_eq!((after.x, after.y), (pinned.x, pinned.y));
+
+        // Syncing a new table only places that table
+        let before: Vec<(f64, f64)> = app.schema.entities.values().map(|e| (e.position.x, e.position.y)).collect();
+        app.sql_content.push_str("\nCREATE TABLE reviews (id INT PRIMARY KEY, product_id INT REFERENCES products(id));");
+        app.sync_sql_changes();
+        assert!(app.schema.entities.contains_key("reviews"));
+        let kept: Vec<(f64, f64)> = app.schema.entities.values().take(before.len()).map(|e| (e.position.x, e.position.y)).collect();
+        assert_eq!(kept, before);
+    }
+
+    #[test]
+    fn test_focus_shows_neighborhood_and_esc_restores() {
+        let mut app = App::new();
+        let positions: Vec<(f64, f64)> = app.schema.entities.values().map(|e| (e.position.x, e.position.y)).collect();
+        app.selected_entity = Some("orders".to_string());
+        app.handle_key(KeyEvent::from(KeyCode::Char('o')));
+
+        let mut names: Vec<&str> = app.schema.entities.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["order_items", "orders", "users"]);
+        assert_eq!(app.schema.relationships.len(), 2);
+        // order_items also references products, which is out of view
+        let stubs = &app.focus.as_ref().unwrap().stubs;
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(stubs["order_items"], 1);
+
+        // A bare Delete deletes nothing, so the focus stays
+        app.handle_key(KeyEvent::from(KeyCode::Delete));
+        assert!(app.focus.is_some());
+
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT));
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.focus.is_none());
+        let restored: Vec<(f64, f64)> = app.schema.entities.values().map(|e| (e.position.x, e.position.y)).collect();
+        assert_eq!(restored, positions);
+        assert_eq!(app.schema.entities.len(), 5);
+    }
+
+    #[test]
+    fn test_search_jumps_to_entity_and_column() {
+        let mut app = App::new();
+        app.camera.set_viewport(ratatui::layout::Rect::new(0, 0, 100, 40));
+        app.handle_key(KeyEvent::from(KeyCode::Char('/')));
+        assert_eq!(app.mode, AppMode::Search);
+        for c in "ordsttus".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(app.search_matches[0].label(), "orders.status");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(app.mode, AppMode::DiagramView);
+        assert_eq!(app.selected_entity.as_deref(), Some("orders"));
+        assert_eq!(app.selected_column.as_deref(), Some("status"));
+        let orders = &app.schema.entities["orders"];
+        let (x, y) = app.camera.to_screen(
+            orders.position.x + orders.dimensions.width as f64 * crate::layout::UNITS_PER_COLUMN / 2.0,
+            orders.position.y + orders.dimensions.height as f64 * crate::layout::UNITS_PER_ROW / 2.0,
+        );
+        assert_eq!((x, y), (50, 20));
+
+        // Esc leaves the selection alone
+        app.handle_key(KeyEvent::from(KeyCode::Char('/')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('u')));
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(app.selected_entity.as_deref(), Some("orders"));
+    }
+
+    #[test]
+    fn test_directional_selection_and_foreign_keys() {
+        let mut app = App::new();
+        for (name, x, y) in [("users", 0.0, 0.0), ("orders", 400.0, 0.0), ("order_items", 400.0, 400.0), ("products", 0.0, 400.0), ("categories", 2000.0, 2000.0)] {
+            app.schema.entities[name].position = Position { x, y };
+        }
+        app.selected_entity = Some("users".to_string());
+        app.handle_key(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT));
+        assert_eq!(app.selected_entity.as_deref(), Some("orders"));
+        app.handle_key(KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT));
+        assert_eq!(app.selected_entity.as_deref(), Some("order_items"));
+        app.handle_key(KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT));
+        assert_eq!(app.selected_entity.as_deref(), Some("products"));
+
+        // order_items: id, order_id, product_id, ...
+        app.selected_entity = Some("order_items".to_string());
+        app.handle_key(KeyEvent::from(KeyCode::Char('.')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('.')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('.')));
+        assert_eq!(app.selected_column.as_deref(), Some("product_id"));
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.selected_entity.as_deref(), Some("products"));
+        assert_eq!(app.selected_column.as_deref(), Some("id"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.selected_entity.as_deref(), Some("categories"));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(app.selected_entity.as_deref(), Some("order_items"));
+        assert_eq!(app.selected_column.as_deref(), Some("product_id"));
+    }
+
+    #[test]
+    fn test_undo_redo_delete_move_and_sync() {
+        let mut app = App::new();
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        let relationships = app.schema.relationships.len();
+
+        app.selected_entity = Some("orders".to_string());
+        let start = app.schema.entities["orders"].position;
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT));
+        app.handle_key(ctrl('d'));
+        assert!(!app.schema.entities.contains_key("orders"));
+        assert_eq!(app.history.done().len(), 2);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('u')));
+        assert!(app.schema.entities.contains_key("orders"));
+        assert_eq!(app.schema.relationships.len(), relationships);
+        app.handle_key(KeyEvent::from(KeyCode::Char('u')));
+        assert_eq!(app.schema.entities["orders"].position, start);
+
+        app.handle_key(ctrl('r'));
+        app.handle_key(ctrl('r'));
+        assert!(!app.schema.entities.contains_key("orders"));
+        assert_eq!(app.history.undone().len(), 0);
+
+        // A sync is undone in one step, and a new edit drops the redo
+        app.sql_content.push_str("\nCREATE TABLE reviews (id INT PRIMARY KEY);");
+        app.sync_sql_changes();
+        assert_eq!(app.history.done().last().unwrap().label, "Sync SQL");
+        app.handle_key(KeyEvent::from(KeyCode::Char('u')));
+        assert!(!app.schema.entities.contains_key("reviews"));
+        assert!(!app.sql_content.contains("reviews"));
+        app.handle_key(ctrl('r'));
+        assert!(app.sql_content.contains("reviews"));
+        app.handle_key(KeyEvent::from(KeyCode::Char('u')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('r')));
+        assert_eq!(app.history.undone().len(), 0);
+        assert_eq!(app.history.done().last().unwrap().label, "Re-layout");
+    }
+
+    #[test]
+    fn test_mouse_selects_drags_and_zooms() {
+        use crate::render::DiagramRenderer;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = App::new();
+        let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal
+                .draw(|f| {
+                    DiagramRenderer::new().with_camera(app.camera).render(
+                        f,
+                        &app.schema,
+                        f.area(),
+                        &app.selected_entity,
+                        &mut app.route_cache,
+                        &mut app.hit_map,
+                    )
+                })
+                .unwrap();
+        };
+        draw(&mut app);
+
+        let mouse = |kind, column, row| MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE };
+        let (column, row) = (0..160u16)
+            .flat_map(|x| (0..50u16).map(move |y| (x, y)))
+            .find(|&(x, y)| app.hit_map.entity_at(x, y) == Some("orders"))
+            .unwrap();
+
+        // Click and drag three cells right
+        let before = app.schema.entities["orders"].position;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), column, row));
+        assert_eq!(app.selected_entity.as_deref(), Some("orders"));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), column + 3, row));
+        app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), column + 3, row));
+        let after = app.schema.entities["orders"].position;
+        assert_eq!(after.x - before.x, 3.0 * crate::layout::UNITS_PER_COLUMN);
+        assert_eq!(after.y, before.y);
+
+        // Click on a relationship line
+        draw(&mut app);
+        let (column, row) = (0..160u16)
+            .flat_map(|x| (0..50u16).map(move |y| (x, y)))
+            .find(|&(x, y)| app.hit_map.entity_at(x, y).is_none() && app.hit_map.relationship_at(x, y).is_some())
+            .unwrap();
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), column, row));
+        assert!(app.selected_relationship.is_some());
+
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown, 0, 0));
+        assert!(app.camera.scale() < 1.0);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::models::{Position, Schema};
+
+/// Oldest operations are forgotten past this many; each schema edit keeps
+/// two copies of the schema.
+const MAX_OPERATIONS: usize = 100;
+
+/// An edit to the diagram, with what it takes to reverse it.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// An entity dragged or nudged
+    Move { entity: String, from: Position, to: Position },
+    /// An entity pinned or unpinned; undoing toggles it back
+    Pin { entity: String },
+    /// Every entity's position before and after a re-layout
+    Layout { before: Vec<(String, Position)>, after: Vec<(String, Position)> },
+    /// Tables added, removed or changed; the whole schema is kept, along
+    /// with the editor text it goes with
+    Schema { before: Box<Schema>, after: Box<Schema>, sql_before: String, sql_after: String },
+}
+
+impl Change {
+    /// Positions of all entities, for a `Layout` change.
+    pub fn positions(schema: &Schema) -> Vec<(String, Position)> {
+        schema.entities.iter().map(|(name, entity)| (name.clone(), entity.position)).collect()
+    }
+
+    fn apply(&self, schema: &mut Schema, sql: &mut String, forward: bool) {
+        match self {
+            Change::Move { entity, from, to } => {
+                if let Some(entity) = schema.entities.get_mut(entity) {
+                    entity.position = if forward { *to } else { *from };
+                }
+            }
+            Change::Pin { entity } => {
+                if let Some(entity) = schema.entities.get_mut(entity) {
+                    entity.pinned = !entity.pinned;
+                }
+            }
+            Change::Layout { before, after } => {
+                for (name, position) in if forward { after } else { before } {
+                    if let Some(entity) = schema.entities.get_mut(name) {
+                        entity.position = *position;
+                    }
+                }
+            }
+            Change::Schema { before, after, sql_before, sql_after } => {
+                *schema = if forward { after } else { before }.as_ref().clone();
+                *sql = if forward { sql_after } else { sql_before }.clone();
+            }
+        }
+    }
+}
+
+/// A change and how it is listed in the history panel.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub label: String,
+    change: Change,
+}
+
+/// Undo and redo stacks of the edits made in the diagram view.
+#[derive(Debug, Default)]
+pub struct History {
+    done: Vec<Operation>,
+    undone: Vec<Operation>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember an edit that was just made. Anything undone before it can
+    /// no longer be redone.
+    pub fn record(&mut self, label: impl Into<String>, change: Change) {
+        self.done.push(Operation { label: label.into(), change });
+        if self.done.len() > MAX_OPERATIONS {
+            self.done.remove(0);
+        }
+        self.undone.clear();
+    }
+
+    /// Reverse the last edit, returning its label. Schema changes also put
+    /// back the editor text.
+    pub fn undo(&mut self, schema: &mut Schema, sql: &mut String) -> Option<&str> {
+        let operation = self.done.pop()?;
+        operation.change.apply(schema, sql, false);
+        self.undone.push(operation);
+        self.undone.last().map(|operation| operation.label.as_str())
+    }
+
+    /// Make the last undone edit again, returning its label.
+    pub fn redo(&mut self, schema: &mut Schema, sql: &mut String) -> Option<&str> {
+        let operation = self.undone.pop()?;
+        operation.change.apply(schema, sql, true);
+        self.done.push(operation);
+        self.done.last().map(|operation| operation.label.as_str())
+    }
+
+    /// Edits that can be undone, oldest first.
+    pub fn done(&self) -> &[Operation] {
+        &self.done
+    }
+
+    /// Edits that can be redone, the next one to redo last.
+    pub fn undone(&self) -> &[Operation] {
+        &self.undone
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Entity;
+
+    fn schema_with(names: &[&str]) -> Schema {
+        let mut schema = Schema::new();
+        for name in names {
+            schema.entities.insert(name.to_string(), Entity { name: name.to_string(), ..Default::default() });
+        }
+        schema
+    }
+
+    #[test]
+    fn test_undo_redo_and_new_edits_drop_redo() {
+        let mut schema = schema_with(&["a", "b"]);
+        let mut sql = "CREATE TABLE a (); CREATE TABLE b ();".to_string();
+        let mut history = History::new();
+
+        let before = Box::new(schema.clone());
+        schema.entities.shift_remove("b");
+        let sql_before = std::mem::replace(&mut sql, "CREATE TABLE a ();".to_string());
+        history.record(
+            "Delete b",
+            Change::Schema { before, after: Box::new(schema.clone()), sql_before, sql_after: sql.clone() },
+        );
+
+        let from = schema.entities["a"].position;
+        let to = Position { x: 40.0, y: 16.0 };
+        schema.entities["a"].position = to;
+        history.record("Move a", Change::Move { entity: "a".to_string(), from, to });
+
+        assert_eq!(history.undo(&mut schema, &mut sql), Some("Move a"));
+        assert_eq!(schema.entities["a"].position.x, 0.0);
+        assert_eq!(history.undo(&mut schema, &mut sql), Some("Delete b"));
+        assert!(schema.entities.contains_key("b"));
+        assert_eq!(sql, "CREATE TABLE a (); CREATE TABLE b ();");
+        assert_eq!(history.undo(&mut schema, &mut sql), None);
+
+        assert_eq!(history.redo(&mut schema, &mut sql), Some("Delete b"));
+        assert!(!schema.entities.contains_key("b"));
+        assert_eq!(sql, "CREATE TABLE a ();");
+        assert_eq!(history.undone().len(), 1);
+
+        schema.entities["a"].pinned = true;
+        history.record("Pin a", Change::Pin { entity: "a".to_string() });
+        assert!(history.undone().is_empty());
+        history.undo(&mut schema, &mut sql);
+        assert!(!schema.entities["a"].pinned);
+    }
+}
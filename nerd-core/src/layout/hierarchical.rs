@@ -0,0 +1,365 @@
+use super::{LayoutEngine, UNITS_PER_COLUMN, UNITS_PER_ROW};
+use crate::models::{Position, Schema};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Gaps between boxes, in cells.
+const LAYER_GAP_ROWS: f64 = 4.0;
+const NODE_GAP_COLUMNS: f64 = 6.0;
+/// Width reserved for an edge passing through a layer.
+const DUMMY_WIDTH_COLUMNS: f64 = 2.0;
+const ORDERING_SWEEPS: usize = 8;
+
+/// A node in the layered graph: a table, or a placeholder where a long edge
+/// crosses a layer.
+#[derive(Debug, Clone)]
+struct Node {
+    entity: Option<String>,
+    width: f64,
+    height: f64,
+    layer: usize,
+}
+
+impl LayoutEngine {
+    /// Sugiyama-style layout: referenced tables on top, each foreign key
+    /// pointing up one or more layers. Fixed entities stay where they are.
+    pub(super) fn layout_hierarchical(&self, schema: &mut Schema, fixed: &HashSet<String>) {
+        let names: Vec<String> = schema.entities.keys().cloned().collect::<BTreeSet<_>>().into_iter().collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+
+        // child -> parent edges, without self references or duplicates
+        let edges: BTreeSet<(usize, usize)> = schema
+            .relationships
+            .iter()
+            .filter_map(|r| Some((*index.get(r.from_table.as_str())?, *index.get(r.to_table.as_str())?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        let edges = break_cycles(names.len(), &edges);
+        let layers = assign_layers(names.len(), &edges);
+
+        let mut nodes: Vec<Node> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let dimensions = schema.entities[name].dimensions;
+                Node {
+                    entity: Some(name.clone()),
+                    width: dimensions.width as f64,
+                    height: dimensions.height as f64,
+                    layer: layers[i],
+                }
+            })
+            .collect();
+
+        // Split edges spanning several layers so every edge joins adjacent layers
+        let mut links: Vec<(usize, usize)> = Vec::new();
+        for &(child, parent) in &edges {
+            let mut lower = child;
+            for layer in (layers[parent] + 1..layers[child]).rev() {
+                nodes.push(Node { entity: None, width: DUMMY_WIDTH_COLUMNS, height: 0.0, layer });
+                let dummy = nodes.len() - 1;
+                links.push((lower, dummy));
+                lower = dummy;
+            }
+            links.push((lower, parent));
+        }
+
+        let order = order_layers(&nodes, &links);
+        let positions = assign_coordinates(&nodes, &links, &order);
+
+        self.place_within_canvas(schema, &nodes, &positions, fixed);
+    }
+
+    /// Convert cell coordinates to canvas units. The spacing is kept as is
+    /// even when the diagram outgrows the canvas, since shrinking it would
+    /// make the boxes overlap. With fixed entities, the rest of the layout
+    /// is moved by their average offset so new tables land next to the
+    /// tables they were laid out with.
+    fn place_within_canvas(&self, schema: &mut Schema, nodes: &[Node], positions: &[(f64, f64)], fixed: &HashSet<String>) {
+        let margin = 50.0;
+        let placed: Vec<(&String, Position)> = nodes
+            .iter()
+            .zip(positions)
+            .filter_map(|(node, &(x, y))| {
+                let name = node.entity.as_ref()?;
+                Some((name, Position { x: margin + x * UNITS_PER_COLUMN, y: margin + y * UNITS_PER_ROW }))
+            })
+            .collect();
+
+        let offsets: Vec<(f64, f64)> = placed
+            .iter()
+            .filter(|(name, _)| fixed.contains(*name))
+            .map(|(name, position)| {
+                let actual = schema.entities[*name].position;
+                (actual.x - position.x, actual.y - position.y)
+            })
+            .collect();
+        let (offset_x, offset_y) = if offsets.is_empty() {
+            (0.0, 0.0)
+        } else {
+            let count = offsets.len() as f64;
+            (offsets.iter().map(|o| o.0).sum::<f64>() / count, offsets.iter().map(|o| o.1).sum::<f64>() / count)
+        };
+
+        for (name, position) in placed {
+            if fixed.contains(name) {
+                continue;
+            }
+            if let Some(entity) = schema.entities.get_mut(name) {
+                entity.position = Position { x: position.x + offset_x, y: position.y + offset_y };
+            }
+        }
+    }
+}
+
+/// Reverse every edge that closes a cycle (a back edge in a depth-first
+/// search), so the graph becomes a DAG.
+fn break_cycles(count: usize, edges: &BTreeSet<(usize, usize)>) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        OnStack,
+        Done,
+    }
+
+    let mut outgoing = vec![Vec::new(); count];
+    for &(from, to) in edges {
+        outgoing[from].push(to);
+    }
+
+    let mut marks = vec![Mark::New; count];
+    let mut reversed = BTreeSet::new();
+    for root in 0..count {
+        if marks[root] != Mark::New {
+            continue;
+        }
+        // Iterative DFS: (node, next child to visit)
+        let mut stack = vec![(root, 0)];
+        marks[root] = Mark::OnStack;
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&child) = outgoing[node].get(next) {
+                stack.push((node, next + 1));
+                match marks[child] {
+                    Mark::New => {
+                        marks[child] = Mark::OnStack;
+                        stack.push((child, 0));
+                    }
+                    Mark::OnStack => {
+                        reversed.insert((node, child));
+                    }
+                    Mark::Done => {}
+                }
+            } else {
+                marks[node] = Mark::Done;
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .map(|&(from, to)| if reversed.contains(&(from, to)) { (to, from) } else { (from, to) })
+        // A pair referencing each other collapses into one edge
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Longest path from the top: tables nothing is referenced from sit in layer
+/// 0, and every child sits below all of its parents.
+fn assign_layers(count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut pending_parents = vec![0; count];
+    let mut children = vec![Vec::new(); count];
+    for &(child, parent) in edges {
+        pending_parents[child] += 1;
+        children[parent].push(child);
+    }
+
+    let mut layers = vec![0; count];
+    let mut ready: Vec<usize> = (0..count).filter(|&n| pending_parents[n] == 0).collect();
+    while let Some(node) = ready.pop() {
+        for &child in &children[node] {
+            layers[child] = layers[child].max(layers[node] + 1);
+            pending_parents[child] -= 1;
+            if pending_parents[child] == 0 {
+                ready.push(child);
+            }
+        }
+    }
+    layers
+}
+
+/// Order each layer by the barycenter of its neighbours in the adjacent
+/// layer, sweeping down and up to reduce crossings.
+fn order_layers(nodes: &[Node], links: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let layer_count = nodes.iter().map(|n| n.layer + 1).max().unwrap_or(0);
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (i, node) in nodes.iter().enumerate() {
+        order[node.layer].push(i);
+    }
+
+    let mut above = vec![Vec::new(); nodes.len()];
+    let mut below = vec![Vec::new(); nodes.len()];
+    for &(child, parent) in links {
+        above[child].push(parent);
+        below[parent].push(child);
+    }
+
+    let mut best = order.clone();
+    let mut best_crossings = count_crossings(&order, &above);
+    for sweep in 0..ORDERING_SWEEPS {
+        if sweep % 2 == 0 {
+            for layer in 1..layer_count {
+                reorder_by_barycenter(&mut order, layer, layer - 1, &above);
+            }
+        } else {
+            for layer in (0..layer_count.saturating_sub(1)).rev() {
+                reorder_by_barycenter(&mut order, layer, layer + 1, &below);
+            }
+        }
+
+        let crossings = count_crossings(&order, &above);
+        if crossings < best_crossings {
+            best = order.clone();
+            best_crossings = crossings;
+        }
+    }
+    best
+}
+
+fn reorder_by_barycenter(order: &mut [Vec<usize>], layer: usize, fixed: usize, neighbours: &[Vec<usize>]) {
+    let slot: HashMap<usize, usize> = order[fixed].iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let mut keyed: Vec<(f64, usize)> = order[layer]
+        .iter()
+        .enumerate()
+        .map(|(current, &node)| {
+            let slots: Vec<usize> = neighbours[node].iter().filter_map(|n| slot.get(n).copied()).collect();
+            // Nodes without neighbours keep their place
+            let barycenter = if slots.is_empty() {
+                current as f64
+            } else {
+                slots.iter().sum::<usize>() as f64 / slots.len() as f64
+            };
+            (barycenter, node)
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    order[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+}
+
+fn count_crossings(order: &[Vec<usize>], above: &[Vec<usize>]) -> usize {
+    let mut crossings = 0;
+    for layer in 1..order.len() {
+        let slot: HashMap<usize, usize> = order[layer - 1].iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let segments: Vec<(usize, usize)> = order[layer]
+            .iter()
+            .enumerate()
+            .flat_map(|(i, node)| above[*node].iter().filter_map(|p| slot.get(p)).map(move |&p| (i, p)))
+            .collect();
+        for (a, &(a_low, a_high)) in segments.iter().enumerate() {
+            for &(b_low, b_high) in &segments[a + 1..] {
+                if (a_low < b_low && a_high > b_high) || (a_low > b_low && a_high < b_high) {
+                    crossings += 1;
+                }
+            }
+        }
+    }
+    crossings
+}
+
+/// Top-left corner of every node, in cells. Layers are stacked by their
+/// tallest box; within a layer, nodes are pulled towards the centre of
+/// their parents while keeping their order and spacing.
+fn assign_coordinates(nodes: &[Node], links: &[(usize, usize)], order: &[Vec<usize>]) -> Vec<(f64, f64)> {
+    let mut above = vec![Vec::new(); nodes.len()];
+    for &(child, parent) in links {
+        above[child].push(parent);
+    }
+
+    let mut positions = vec![(0.0, 0.0); nodes.len()];
+    let mut y = 0.0;
+    for layer in order {
+        let mut next_free = 0.0;
+        for &node in layer {
+            let parent_centres: Vec<f64> = above[node]
+                .iter()
+                .map(|&p| positions[p].0 + nodes[p].width / 2.0)
+                .collect();
+            let wanted = if parent_centres.is_empty() {
+                next_free
+            } else {
+                parent_centres.iter().sum::<f64>() / parent_centres.len() as f64 - nodes[node].width / 2.0
+            };
+            let x = wanted.max(next_free);
+            positions[node] = (x, y);
+            next_free = x + nodes[node].width + NODE_GAP_COLUMNS;
+        }
+        let height = layer.iter().map(|&n| nodes[n].height).fold(0.0, f64::max);
+        y += height + LAYER_GAP_ROWS;
+    }
+
+    // Parents may have been pushed right of centre; start everything at 0
+    let min_x = positions.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    if min_x.is_finite() {
+        for position in positions.iter_mut() {
+            position.0 -= min_x;
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::{LayoutAlgorithm, LayoutEngine};
+    use crate::parser::{SqlDialect, SqlParser};
+
+    fn layout(sql: &str) -> crate::models::Schema {
+        let mut schema = SqlParser::new(SqlDialect::Generic).parse_sql(sql).unwrap();
+        let mut engine = LayoutEngine::new(800.0, 600.0);
+        engine.set_algorithm(LayoutAlgorithm::Hierarchical);
+        engine.layout_entities(&mut schema);
+        schema
+    }
+
+    #[test]
+    fn test_referenced_tables_on_top() {
+        let schema = layout(
+            "CREATE TABLE users (id INT PRIMARY KEY);
+             CREATE TABLE products (id INT PRIMARY KEY);
+             CREATE TABLE orders (id INT PRIMARY KEY, user_id INT REFERENCES users(id));
+             CREATE TABLE order_items (id INT PRIMARY KEY, order_id INT REFERENCES orders(id), product_id INT REFERENCES products(id));",
+        );
+        let y = |name: &str| schema.entities[name].position.y;
+
+        assert_eq!(y("users"), y("products"));
+        assert!(y("orders") > y("users"));
+        assert!(y("order_items") > y("orders"));
+    }
+
+    #[test]
+    fn test_barycenter_ordering_removes_crossing() {
+        // Alphabetical order would put x under a and y under b
+        let schema = layout(
+            "CREATE TABLE a (id INT PRIMARY KEY);
+             CREATE TABLE b (id INT PRIMARY KEY);
+             CREATE TABLE x (id INT PRIMARY KEY, b_id INT REFERENCES b(id));
+             CREATE TABLE y (id INT PRIMARY KEY, a_id INT REFERENCES a(id));",
+        );
+        let x = |name: &str| schema.entities[name].position.x;
+
+        assert!(x("a") < x("b"));
+        assert!(x("y") < x("x"));
+    }
+
+    #[test]
+    fn test_cycles_are_broken() {
+        let schema = layout(
+            "CREATE TABLE a (id INT PRIMARY KEY, b_id INT);
+             CREATE TABLE b (id INT PRIMARY KEY, a_id INT REFERENCES a(id));
+             ALTER TABLE a ADD FOREIGN KEY (b_id) REFERENCES b(id);
+             CREATE TABLE c (id INT PRIMARY KEY, c_id INT REFERENCES c(id), a_id INT REFERENCES a(id));",
+        );
+        let y = |name: &str| schema.entities[name].position.y;
+
+        assert_ne!(y("a"), y("b"));
+        assert!(y("c") > y("a"));
+    }
+}
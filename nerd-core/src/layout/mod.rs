@@ -1,28 +1,100 @@
-use crate::models::{Entity, Position, Schema, Relationship};
-use petgraph::{Graph, Undirected};
-use std::collections::HashMap;
+use crate::models::{Entity, Position, Schema};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
 
+mod hierarchical;
+mod overlap;
+
+/// Canvas units per terminal column and row. The 800x600 canvas maps to
+/// roughly a 100x37 terminal.
+pub const UNITS_PER_COLUMN: f64 = 8.0;
+pub const UNITS_PER_ROW: f64 = 16.0;
+
+/// Force-directed model, in canvas units.
+const REPULSION_STRENGTH: f64 = 5000.0;
+/// Distance past which repulsion is ignored.
+const REPULSION_RANGE: f64 = 300.0;
+const ATTRACTION_STRENGTH: f64 = 100.0;
+const IDEAL_DISTANCE: f64 = 150.0;
+const DAMPING: f64 = 0.1;
+const MAX_VELOCITY: f64 = 10.0;
+const MAX_ITERATIONS: usize = 100;
+/// Stop once no entity moves further than this in one iteration.
+const CONVERGED_STEP: f64 = 0.1;
+const MARGIN: f64 = 50.0;
+/// How much more canvas area than the boxes cover a force layout gets.
+const SPARE_AREA: f64 = 4.0;
+
+/// How entities are arranged on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutAlgorithm {
+    /// Spring model: related tables attract, all tables repel
+    #[default]
+    ForceDirected,
+    /// Layers following the foreign keys, referenced tables on top
+    Hierarchical,
+}
+
+/// Lays out a schema on a `width` x `height` canvas. The same schema and
+/// seed always produce the same positions.
 pub struct LayoutEngine {
     width: f64,
     height: f64,
+    algorithm: LayoutAlgorithm,
+    seed: u64,
 }
 
 impl LayoutEngine {
     pub fn new(width: f64, height: f64) -> Self {
-        Self { width, height }
+        Self { width, height, algorithm: LayoutAlgorithm::default(), seed: 0 }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
     }
 
+    pub fn algorithm(&self) -> LayoutAlgorithm {
+        self.algorithm
+    }
+
+    pub fn set_algorithm(&mut self, algorithm: LayoutAlgorithm) {
+        self.algorithm = algorithm;
+    }
+
+    /// Position every entity except the pinned ones, then push apart any
+    /// boxes that ended up overlapping.
     pub fn layout_entities(&self, schema: &mut Schema) {
-        if schema.entities.is_empty() {
+        self.layout_around(schema, &HashSet::new());
+    }
+
+    /// Place only the entities that are not in `placed` and not pinned,
+    /// leaving the rest of the diagram where it is.
+    pub fn layout_new_entities(&self, schema: &mut Schema, placed: &HashSet<String>) {
+        self.layout_around(schema, placed);
+    }
+
+    fn layout_around(&self, schema: &mut Schema, placed: &HashSet<String>) {
+        let fixed: HashSet<String> = schema
+            .entities
+            .values()
+            .filter(|entity| entity.pinned || placed.contains(&entity.name))
+            .map(|entity| entity.name.clone())
+            .collect();
+        if schema.entities.len() == fixed.len() {
             return;
         }
 
         if schema.entities.len() == 1 {
             self.layout_single_entity(schema);
-        } else {
-            self.layout_force_directed(schema);
+            return;
+        }
+
+        match self.algorithm {
+            LayoutAlgorithm::ForceDirected => self.layout_force_directed(schema, &fixed),
+            LayoutAlgorithm::Hierarchical => self.layout_hierarchical(schema, &fixed),
         }
+        overlap::remove_overlaps(schema, &fixed);
     }
 
     fn layout_single_entity(&self, schema: &mut Schema) {
@@ -34,179 +106,184 @@ impl LayoutEngine {
         }
     }
 
-    fn layout_force_directed(&self, schema: &mut Schema) {
-        let mut graph = Graph::<String, (), Undirected>::new_undirected();
-        let mut node_indices = HashMap::new();
-        
-        for entity_name in schema.entities.keys() {
-            let node_index = graph.add_node(entity_name.clone());
-            node_indices.insert(entity_name.clone(), node_index);
-        }
-        
-        for relationship in &schema.relationships {
-            if let (Some(&from_idx), Some(&to_idx)) = (
-                node_indices.get(&relationship.from_table),
-                node_indices.get(&relationship.to_table),
-            ) {
-                graph.add_edge(from_idx, to_idx, ());
-            }
-        }
-        
-        let mut positions: HashMap<String, Position> = HashMap::new();
-        
-        self.initialize_positions(&mut positions, &schema.entities);
-        
-        for _ in 0..100 {
-            self.apply_forces(&mut positions, &schema.entities, &schema.relationships);
-        }
-        
-        for (entity_name, position) in positions {
-            if let Some(entity) = schema.entities.get_mut(&entity_name) {
-                entity.position = position;
+    /// Fixed entities start where they are and never move, but still push
+    /// and pull the others. Stops early once no entity moves noticeably.
+    fn layout_force_directed(&self, schema: &mut Schema, fixed: &HashSet<String>) {
+        let index: HashMap<&str, usize> = schema.entities.keys().enumerate().map(|(i, name)| (name.as_str(), i)).collect();
+        let edges: Vec<(usize, usize)> = schema
+            .relationships
+            .iter()
+            .filter_map(|r| Some((*index.get(r.from_table.as_str())?, *index.get(r.to_table.as_str())?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        let movable: Vec<bool> = schema.entities.keys().map(|name| !fixed.contains(name)).collect();
+        let canvas = self.canvas_for(&schema.entities);
+
+        let mut positions = self.initialize_positions(&schema.entities, fixed, canvas);
+        let mut forces = vec![(0.0, 0.0); positions.len()];
+
+        for _ in 0..MAX_ITERATIONS {
+            forces.fill((0.0, 0.0));
+            apply_repulsion_forces(&positions, &mut forces);
+            apply_attraction_forces(&positions, &mut forces, &edges);
+
+            let largest_step = self.apply_forces_to_positions(&mut positions, &forces, &movable, canvas);
+            if largest_step < CONVERGED_STEP {
+                break;
             }
         }
-    }
 
-    fn initialize_positions(&self, positions: &mut HashMap<String, Position>, entities: &HashMap<String, Entity>) {
-        let entity_count = entities.len() as f64;
-        let radius = (self.width.min(self.height) / 4.0).min(200.0);
-        let center_x = self.width / 2.0;
-        let center_y = self.height / 2.0;
-
-        for (i, entity_name) in entities.keys().enumerate() {
-            let angle = 2.0 * PI * i as f64 / entity_count;
-            let x = center_x + radius * angle.cos();
-            let y = center_y + radius * angle.sin();
-            
-            positions.insert(entity_name.clone(), Position { x, y });
+        for (entity, position) in schema.entities.values_mut().zip(positions) {
+            entity.position = position;
         }
     }
 
-    fn apply_forces(
-        &self,
-        positions: &mut HashMap<String, Position>,
-        entities: &HashMap<String, Entity>,
-        relationships: &[Relationship],
-    ) {
-        let mut forces: HashMap<String, (f64, f64)> = HashMap::new();
-        
-        for entity_name in entities.keys() {
-            forces.insert(entity_name.clone(), (0.0, 0.0));
-        }
-        
-        self.apply_repulsion_forces(positions, &mut forces);
-        
-        self.apply_attraction_forces(positions, &mut forces, relationships);
-        
-        self.apply_forces_to_positions(positions, &forces);
-        
-        self.keep_within_bounds(positions);
+    /// The canvas, grown for schemas whose boxes would not fit on it with
+    /// room to spare.
+    fn canvas_for(&self, entities: &IndexMap<String, Entity>) -> (f64, f64) {
+        let needed: f64 = entities
+            .values()
+            .map(|e| e.dimensions.width as f64 * UNITS_PER_COLUMN * e.dimensions.height as f64 * UNITS_PER_ROW)
+            .sum();
+        let scale = (SPARE_AREA * needed / (self.width * self.height)).sqrt().max(1.0);
+        (self.width * scale, self.height * scale)
     }
 
-    fn apply_repulsion_forces(
-        &self,
-        positions: &HashMap<String, Position>,
-        forces: &mut HashMap<String, (f64, f64)>,
-    ) {
-        let repulsion_strength = 5000.0;
-        
-        for (entity1, pos1) in positions {
-            for (entity2, pos2) in positions {
-                if entity1 == entity2 {
-                    continue;
-                }
-                
-                let dx = pos1.x - pos2.x;
-                let dy = pos1.y - pos2.y;
-                let distance = (dx * dx + dy * dy).sqrt().max(1.0);
-                
-                let force = repulsion_strength / (distance * distance);
-                let fx = (dx / distance) * force;
-                let fy = (dy / distance) * force;
-                
-                if let Some((curr_fx, curr_fy)) = forces.get_mut(entity1) {
-                    *curr_fx += fx;
-                    *curr_fy += fy;
+    /// Spread over a disc in declaration order, along a sunflower spiral so
+    /// any number of tables starts evenly spaced. The seed turns the spiral
+    /// and nudges each table, so different seeds give different layouts.
+    fn initialize_positions(&self, entities: &IndexMap<String, Entity>, fixed: &HashSet<String>, (width, height): (f64, f64)) -> Vec<Position> {
+        let entity_count = entities.len() as f64;
+        let radius = width.min(height) / 4.0;
+        let golden_angle = PI * (3.0 - 5.0f64.sqrt());
+
+        let mut random = SplitMix64(self.seed);
+        let rotation = 2.0 * PI * random.next_unit();
+
+        entities
+            .iter()
+            .enumerate()
+            .map(|(i, (entity_name, entity))| {
+                let angle = rotation + golden_angle * i as f64;
+                let distance = radius * ((i as f64 + 0.5) / entity_count).sqrt() * (1.0 + 0.1 * (random.next_unit() - 0.5));
+                if fixed.contains(entity_name) {
+                    entity.position
+                } else {
+                    Position {
+                        x: width / 2.0 + distance * angle.cos(),
+                        y: height / 2.0 + distance * angle.sin(),
+                    }
                 }
-            }
-        }
+            })
+            .collect()
     }
 
-    fn apply_attraction_forces(
+    /// Move every movable entity along its force, capped and kept within
+    /// the canvas. Returns the largest step taken.
+    fn apply_forces_to_positions(
         &self,
-        positions: &HashMap<String, Position>,
-        forces: &mut HashMap<String, (f64, f64)>,
-        relationships: &[Relationship],
-    ) {
-        let attraction_strength = 100.0;
-        let ideal_distance = 150.0;
-        
-        for relationship in relationships {
-            if let (Some(pos1), Some(pos2)) = (
-                positions.get(&relationship.from_table),
-                positions.get(&relationship.to_table),
-            ) {
-                let dx = pos2.x - pos1.x;
-                let dy = pos2.y - pos1.y;
-                let distance = (dx * dx + dy * dy).sqrt().max(1.0);
-                
-                let force = attraction_strength * (distance - ideal_distance) / distance;
-                let fx = (dx / distance) * force;
-                let fy = (dy / distance) * force;
-                
-                if let Some((curr_fx, curr_fy)) = forces.get_mut(&relationship.from_table) {
-                    *curr_fx += fx;
-                    *curr_fy += fy;
-                }
-                
-                if let Some((curr_fx, curr_fy)) = forces.get_mut(&relationship.to_table) {
-                    *curr_fx -= fx;
-                    *curr_fy -= fy;
-                }
+        positions: &mut [Position],
+        forces: &[(f64, f64)],
+        movable: &[bool],
+        (width, height): (f64, f64),
+    ) -> f64 {
+        // Larger canvases need longer steps to spread out in time
+        let max_velocity = MAX_VELOCITY * width / self.width;
+        let mut largest_step: f64 = 0.0;
+
+        for ((position, &(fx, fy)), _) in positions.iter_mut().zip(forces).zip(movable).filter(|(_, movable)| **movable) {
+            let mut vx = fx * DAMPING;
+            let mut vy = fy * DAMPING;
+
+            let velocity = (vx * vx + vy * vy).sqrt();
+            if velocity > max_velocity {
+                let scale = max_velocity / velocity;
+                vx *= scale;
+                vy *= scale;
             }
+
+            let x = (position.x + vx).clamp(MARGIN, width - MARGIN);
+            let y = (position.y + vy).clamp(MARGIN, height - MARGIN);
+            largest_step = largest_step.max((x - position.x).abs()).max((y - position.y).abs());
+            *position = Position { x, y };
         }
+        largest_step
     }
+}
 
-    fn apply_forces_to_positions(
-        &self,
-        positions: &mut HashMap<String, Position>,
-        forces: &HashMap<String, (f64, f64)>,
-    ) {
-        let damping = 0.1;
-        let max_velocity = 10.0;
-        
-        for (entity_name, (fx, fy)) in forces {
-            if let Some(position) = positions.get_mut(entity_name) {
-                let vx = fx * damping;
-                let vy = fy * damping;
-                
-                let velocity = (vx * vx + vy * vy).sqrt();
-                if velocity > max_velocity {
-                    let scale = max_velocity / velocity;
-                    position.x += vx * scale;
-                    position.y += vy * scale;
-                } else {
-                    position.x += vx;
-                    position.y += vy;
-                }
+/// Repulsion between entities, which is negligible beyond
+/// `REPULSION_RANGE`. Positions are bucketed into cells of that size so
+/// each entity only looks at its own and the eight neighbouring cells.
+fn apply_repulsion_forces(positions: &[Position], forces: &mut [(f64, f64)]) {
+    let cell = |p: &Position| ((p.x / REPULSION_RANGE).floor() as i64, (p.y / REPULSION_RANGE).floor() as i64);
+    let mut buckets: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, position) in positions.iter().enumerate() {
+        buckets.entry(cell(position)).or_default().push(i);
+    }
+
+    for (i, pos1) in positions.iter().enumerate() {
+        let (cx, cy) = cell(pos1);
+        let neighbours = (cx - 1..=cx + 1)
+            .flat_map(|x| (cy - 1..=cy + 1).map(move |y| (x, y)))
+            .filter_map(|key| buckets.get(&key))
+            .flatten();
+
+        for &j in neighbours {
+            if i == j {
+                continue;
             }
+            let dx = pos1.x - positions[j].x;
+            let dy = pos1.y - positions[j].y;
+            let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+
+            let force = REPULSION_STRENGTH / (distance * distance);
+            forces[i].0 += (dx / distance) * force;
+            forces[i].1 += (dy / distance) * force;
         }
     }
+}
 
-    fn keep_within_bounds(&self, positions: &mut HashMap<String, Position>) {
-        let margin = 50.0;
-        
-        for position in positions.values_mut() {
-            position.x = position.x.clamp(margin, self.width - margin);
-            position.y = position.y.clamp(margin, self.height - margin);
-        }
+/// Springs along the relationships, pulling towards `IDEAL_DISTANCE`.
+fn apply_attraction_forces(positions: &[Position], forces: &mut [(f64, f64)], edges: &[(usize, usize)]) {
+    for &(from, to) in edges {
+        let dx = positions[to].x - positions[from].x;
+        let dy = positions[to].y - positions[from].y;
+        let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+
+        let force = ATTRACTION_STRENGTH * (distance - IDEAL_DISTANCE) / distance;
+        let fx = (dx / distance) * force;
+        let fy = (dy / distance) * force;
+
+        forces[from].0 += fx;
+        forces[from].1 += fy;
+        forces[to].0 -= fx;
+        forces[to].1 -= fy;
+    }
+}
+
+/// Small deterministic generator so layouts do not depend on a `rand` crate
+/// or on process state.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Column, RelationshipType, Dimensions};
+    use crate::models::{Column, ColumnPair, Relationship, RelationshipType, Dimensions};
 
     #[test]
     fn test_single_entity_layout() {
@@ -221,10 +298,12 @@ mod tests {
                     is_primary_key: true,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
             ],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         schema.entities.insert("users".to_string(), entity);
 
@@ -245,6 +324,7 @@ mod tests {
             columns: vec![],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         
         let posts = Entity {
@@ -252,17 +332,19 @@ mod tests {
             columns: vec![],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         
         schema.entities.insert("users".to_string(), users);
         schema.entities.insert("posts".to_string(), posts);
         
         schema.relationships.push(Relationship {
+            name: None,
             from_table: "posts".to_string(),
-            from_column: "user_id".to_string(),
             to_table: "users".to_string(),
-            to_column: "id".to_string(),
+            column_pairs: vec![ColumnPair::new("user_id", "id")],
             relationship_type: RelationshipType::OneToMany,
+            optional: false,
         });
 
         let layout_engine = LayoutEngine::new(800.0, 600.0);
@@ -277,4 +359,117 @@ mod tests {
         assert!(posts_pos.x >= 50.0 && posts_pos.x <= 750.0);
         assert!(posts_pos.y >= 50.0 && posts_pos.y <= 550.0);
     }
-}
\ No newline at end of file
+
+    fn positions(sql: &str, seed: u64) -> Vec<(String, u64, u64)> {
+        let mut schema = crate::parser::SqlParser::new(crate::parser::SqlDialect::Generic).parse_sql(sql).unwrap();
+        let mut layout_engine = LayoutEngine::new(800.0, 600.0);
+        layout_engine.set_seed(seed);
+        layout_engine.layout_entities(&mut schema);
+        schema
+            .entities
+            .values()
+            .map(|e| (e.name.clone(), e.position.x.to_bits(), e.position.y.to_bits()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_is_deterministic_per_seed() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY);
+            CREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));
+            CREATE TABLE comments (id INT PRIMARY KEY, post_id INT REFERENCES posts(id), user_id INT REFERENCES users(id));
+            CREATE TABLE tags (id INT PRIMARY KEY);
+        ";
+
+        let first = positions(sql, 7);
+        assert_eq!(first, positions(sql, 7));
+        assert_ne!(first, positions(sql, 8));
+
+        // Declaration order is kept
+        let names: Vec<&str> = first.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["users", "posts", "comments", "tags"]);
+    }
+
+    #[test]
+    fn test_layouts_leave_no_overlapping_boxes() {
+        let mut sql = String::from("CREATE TABLE hub (id INT PRIMARY KEY);");
+        for i in 0..8 {
+            let columns: Vec<String> = (0..3 + i * 2).map(|c| format!("field_{} VARCHAR(64)", c)).collect();
+            sql.push_str(&format!(
+                "CREATE TABLE spoke_{} (id INT PRIMARY KEY, hub_id INT REFERENCES hub(id), {});",
+                i,
+                columns.join(", ")
+            ));
+        }
+
+        for algorithm in [LayoutAlgorithm::ForceDirected, LayoutAlgorithm::Hierarchical] {
+            let mut schema = crate::parser::SqlParser::new(crate::parser::SqlDialect::Generic).parse_sql(&sql).unwrap();
+            let mut layout_engine = LayoutEngine::new(800.0, 600.0);
+            layout_engine.set_algorithm(algorithm);
+            layout_engine.layout_entities(&mut schema);
+
+            let boxes: Vec<(f64, f64, f64, f64)> = schema
+                .entities
+                .values()
+                .map(|e| {
+                    let x = e.position.x / UNITS_PER_COLUMN;
+                    let y = e.position.y / UNITS_PER_ROW;
+                    (x, y, x + e.dimensions.width as f64, y + e.dimensions.height as f64)
+                })
+                .collect();
+            for (i, a) in boxes.iter().enumerate() {
+                for b in &boxes[i + 1..] {
+                    let apart = a.2 < b.0 || b.2 < a.0 || a.3 < b.1 || b.3 < a.1;
+                    assert!(apart, "{:?}: {:?} overlaps {:?}", algorithm, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pinned_entities_keep_their_position() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY);
+            CREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));
+            CREATE TABLE comments (id INT PRIMARY KEY, post_id INT REFERENCES posts(id));
+        ";
+
+        for algorithm in [LayoutAlgorithm::ForceDirected, LayoutAlgorithm::Hierarchical] {
+            let mut schema = crate::parser::SqlParser::new(crate::parser::SqlDialect::Generic).parse_sql(sql).unwrap();
+            let posts = schema.entities.get_mut("posts").unwrap();
+            posts.position = Position { x: 640.0, y: 420.0 };
+            posts.pinned = true;
+
+            let mut layout_engine = LayoutEngine::new(800.0, 600.0);
+            layout_engine.set_algorithm(algorithm);
+            layout_engine.layout_entities(&mut schema);
+
+            let posts = schema.entities["posts"].position;
+            assert_eq!((posts.x, posts.y), (640.0, 420.0), "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_bucketed_repulsion_matches_pairwise_within_range() {
+        // Scattered over several buckets, every pair closer than the range
+        let positions: Vec<Position> = (0..40)
+            .map(|i| Position { x: 37.0 * (i % 8) as f64 + 290.0, y: 53.0 * (i / 8) as f64 + 10.0 })
+            .collect();
+        let mut forces = vec![(0.0, 0.0); positions.len()];
+        apply_repulsion_forces(&positions, &mut forces);
+
+        for (i, p1) in positions.iter().enumerate() {
+            let (mut fx, mut fy) = (0.0, 0.0);
+            for (j, p2) in positions.iter().enumerate() {
+                let (dx, dy) = (p1.x - p2.x, p1.y - p2.y);
+                let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+                if i != j && distance < REPULSION_RANGE {
+                    fx += dx / distance * REPULSION_STRENGTH / (distance * distance);
+                    fy += dy / distance * REPULSION_STRENGTH / (distance * distance);
+                }
+            }
+            // Pairs beyond the range may or may not be counted; they are tiny
+            assert!((forces[i].0 - fx).abs() < 0.5 && (forces[i].1 - fy).abs() < 0.5);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use super::{UNITS_PER_COLUMN, UNITS_PER_ROW};
+use crate::models::{Position, Schema};
+use std::collections::{HashMap, HashSet};
+
+/// Free space kept to the right of and below every box, in cells, so
+/// relationship lines always have a channel between neighbours.
+const GAP_COLUMNS: f64 = 4.0;
+const GAP_ROWS: f64 = 2.0;
+const SEPARATION_PASSES: usize = 50;
+
+/// An entity's box in canvas units, grown by the gap.
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Bounds {
+    fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    /// How far the two boxes overlap on each axis, if they do.
+    fn overlap(&self, other: &Bounds) -> Option<(f64, f64)> {
+        let dx = self.right().min(other.right()) - self.x.max(other.x);
+        let dy = self.bottom().min(other.bottom()) - self.y.max(other.y);
+        (dx > 0.0 && dy > 0.0).then_some((dx, dy))
+    }
+}
+
+/// Boxes bucketed by their top-left corner, in cells as large as the
+/// largest box, so two boxes can only overlap when their cells touch.
+struct Buckets {
+    size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Buckets {
+    fn new(size: f64) -> Self {
+        Self { size, cells: HashMap::new() }
+    }
+
+    fn key(&self, bounds: &Bounds) -> (i64, i64) {
+        ((bounds.x / self.size).floor() as i64, (bounds.y / self.size).floor() as i64)
+    }
+
+    fn insert(&mut self, index: usize, bounds: &Bounds) {
+        self.cells.entry(self.key(bounds)).or_default().push(index);
+    }
+
+    /// Every box that could overlap `bounds`.
+    fn near(&self, bounds: &Bounds) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cy) = self.key(bounds);
+        (cx - 1..=cx + 1)
+            .flat_map(move |x| (cy - 1..=cy + 1).map(move |y| (x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .copied()
+    }
+}
+
+/// Move entities so that no two boxes intersect and every pair is at least
+/// the gap apart. Overlapping pairs are first pushed apart along the axis
+/// where they overlap least, which keeps the layout's shape; whatever is
+/// still overlapping after that is dropped below the boxes it hits, in
+/// declaration order, which always terminates. Fixed entities never move,
+/// and overlaps between two of them are left alone.
+pub(super) fn remove_overlaps(schema: &mut Schema, fixed: &HashSet<String>) {
+    let movable: Vec<bool> = schema.entities.keys().map(|name| !fixed.contains(name)).collect();
+    let mut boxes: Vec<Bounds> = schema
+        .entities
+        .values()
+        .map(|entity| Bounds {
+            x: entity.position.x,
+            y: entity.position.y,
+            width: (entity.dimensions.width as f64 + GAP_COLUMNS) * UNITS_PER_COLUMN,
+            height: (entity.dimensions.height as f64 + GAP_ROWS) * UNITS_PER_ROW,
+        })
+        .collect();
+
+    let bucket_size = boxes.iter().map(|b| b.width.max(b.height)).fold(1.0, f64::max);
+
+    for _ in 0..SEPARATION_PASSES {
+        let mut buckets = Buckets::new(bucket_size);
+        for (i, bounds) in boxes.iter().enumerate() {
+            buckets.insert(i, bounds);
+        }
+
+        let mut moved = false;
+        for i in 0..boxes.len() {
+            let mut candidates: Vec<usize> = buckets.near(&boxes[i]).filter(|&j| j > i).collect();
+            candidates.sort_unstable();
+            for j in candidates {
+                if !movable[i] && !movable[j] {
+                    continue;
+                }
+                let Some((dx, dy)) = boxes[i].overlap(&boxes[j]) else {
+                    continue;
+                };
+                moved = true;
+                // Split the push between the two, or give it all to the one
+                // that can move
+                let (share_i, share_j) = match (movable[i], movable[j]) {
+                    (true, true) => (0.5, 0.5),
+                    (true, false) => (1.0, 0.0),
+                    _ => (0.0, 1.0),
+                };
+                if dx <= dy {
+                    let direction = if boxes[i].x + boxes[i].width / 2.0 <= boxes[j].x + boxes[j].width / 2.0 { 1.0 } else { -1.0 };
+                    boxes[i].x -= direction * dx * share_i;
+                    boxes[j].x += direction * dx * share_j;
+                } else {
+                    let direction = if boxes[i].y + boxes[i].height / 2.0 <= boxes[j].y + boxes[j].height / 2.0 { 1.0 } else { -1.0 };
+                    boxes[i].y -= direction * dy * share_i;
+                    boxes[j].y += direction * dy * share_j;
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    // Pushing apart can move boxes past the top-left of the canvas
+    for (bounds, _) in boxes.iter_mut().zip(&movable).filter(|(_, movable)| **movable) {
+        bounds.x = bounds.x.max(0.0);
+        bounds.y = bounds.y.max(0.0);
+    }
+
+    // Fixed boxes are settled first, then each movable one in turn
+    let order: Vec<usize> = (0..boxes.len())
+        .filter(|&i| !movable[i])
+        .chain((0..boxes.len()).filter(|&i| movable[i]))
+        .collect();
+    let mut settled = Buckets::new(bucket_size);
+    for i in order {
+        if movable[i] {
+            while let Some(bottom) = settled
+                .near(&boxes[i])
+                .filter(|&j| boxes[i].overlap(&boxes[j]).is_some())
+                .map(|j| boxes[j].bottom())
+                .reduce(f64::max)
+            {
+                boxes[i].y = bottom;
+            }
+        }
+        settled.insert(i, &boxes[i]);
+    }
+
+    for (entity, bounds) in schema.entities.values_mut().zip(&boxes) {
+        entity.position = Position { x: bounds.x, y: bounds.y };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Dimensions, Entity};
+
+    fn entity(name: &str, x: f64, y: f64, width: u16, height: u16) -> Entity {
+        Entity {
+            name: name.to_string(),
+            position: Position { x, y },
+            dimensions: Dimensions { width, height },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_no_boxes_intersect_and_gap_is_kept() {
+        let mut schema = Schema::new();
+        // All stacked on the same spot, with very different sizes
+        for (i, (width, height)) in [(20, 17), (40, 5), (12, 3), (30, 30), (18, 8)].into_iter().enumerate() {
+            let name = format!("t{}", i);
+            schema.entities.insert(name.clone(), entity(&name, 100.0, 100.0, width, height));
+        }
+
+        remove_overlaps(&mut schema, &HashSet::new());
+
+        let cells: Vec<(f64, f64, f64, f64)> = schema
+            .entities
+            .values()
+            .map(|e| {
+                let x = e.position.x / UNITS_PER_COLUMN;
+                let y = e.position.y / UNITS_PER_ROW;
+                (x, y, x + e.dimensions.width as f64, y + e.dimensions.height as f64)
+            })
+            .collect();
+        for (i, a) in cells.iter().enumerate() {
+            assert!(a.0 >= 0.0 && a.1 >= 0.0);
+            for b in &cells[i + 1..] {
+                let apart_x = a.2 + GAP_COLUMNS <= b.0 + 1e-9 || b.2 + GAP_COLUMNS <= a.0 + 1e-9;
+                let apart_y = a.3 + GAP_ROWS <= b.1 + 1e-9 || b.3 + GAP_ROWS <= a.1 + 1e-9;
+                assert!(apart_x || apart_y, "{:?} and {:?} are too close", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_separated_boxes_stay_put() {
+        let mut schema = Schema::new();
+        schema.entities.insert("a".to_string(), entity("a", 0.0, 0.0, 10, 5));
+        schema.entities.insert("b".to_string(), entity("b", 400.0, 0.0, 10, 5));
+
+        remove_overlaps(&mut schema, &HashSet::new());
+
+        assert_eq!(schema.entities["a"].position.x, 0.0);
+        assert_eq!(schema.entities["b"].position.x, 400.0);
+    }
+
+    #[test]
+    fn test_fixed_boxes_do_not_move() {
+        let mut schema = Schema::new();
+        schema.entities.insert("pinned".to_string(), entity("pinned", 80.0, 80.0, 20, 10));
+        schema.entities.insert("free".to_string(), entity("free", 90.0, 90.0, 20, 10));
+        let fixed: HashSet<String> = ["pinned".to_string()].into_iter().collect();
+
+        remove_overlaps(&mut schema, &fixed);
+
+        let pinned = schema.entities["pinned"].position;
+        let free = schema.entities["free"].position;
+        assert_eq!((pinned.x, pinned.y), (80.0, 80.0));
+        let apart_x = free.x >= pinned.x + (20.0 + GAP_COLUMNS) * UNITS_PER_COLUMN;
+        let apart_y = free.y >= pinned.y + (10.0 + GAP_ROWS) * UNITS_PER_ROW;
+        assert!(apart_x || apart_y);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod app;
+pub mod history;
+pub mod layout;
+pub mod migrations;
+pub mod models;
+pub mod parser;
+pub mod render;
+pub mod search;
+pub mod sync;
@@ -1,12 +1,7 @@
-mod app;
-mod models;
-mod parser;
-mod layout;
-mod render;
-mod sync;
-
 use anyhow::Result;
-use app::App;
+use nerd_core::app::{self, App};
+use nerd_core::parser::SqlDialect;
+use nerd_core::{migrations, render};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -17,15 +12,28 @@ use ratatui::{
     Terminal,
 };
 use std::io;
+use std::path::PathBuf;
 
 fn main() -> Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
+
+    let mut app = App::new();
+    if let Some(dialect) = args.dialect {
+        app.set_dialect(dialect);
+    }
+    if let Some(seed) = args.seed {
+        app.set_layout_seed(seed);
+    }
+    if let Some(path) = &args.path {
+        app.load_migrations(load_path(path)?);
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
     let res = run_app(&mut terminal, &mut app);
 
     disable_raw_mode()?;
@@ -43,6 +51,55 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Default)]
+struct Args {
+    dialect: Option<SqlDialect>,
+    seed: Option<u64>,
+    path: Option<PathBuf>,
+}
+
+/// `nerd-core [--dialect <name>] [--seed <n>] [path]`
+///
+/// `--dialect` (or `-d`) forces the SQL dialect instead of detecting it from
+/// the input. `--seed` picks the starting layout. `path` is a SQL file or a
+/// directory of migrations.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dialect" | "-d" => {
+                let name = args.next().ok_or_else(|| anyhow::anyhow!("--dialect requires a value"))?;
+                parsed.dialect = Some(name.parse()?);
+            }
+            other if other.starts_with("--dialect=") => {
+                parsed.dialect = Some(other["--dialect=".len()..].parse()?);
+            }
+            "--seed" => {
+                let seed = args.next().ok_or_else(|| anyhow::anyhow!("--seed requires a value"))?;
+                parsed.seed = Some(seed.parse()?);
+            }
+            other if other.starts_with("--seed=") => {
+                parsed.seed = Some(other["--seed=".len()..].parse()?);
+            }
+            other if !other.starts_with('-') && parsed.path.is_none() => {
+                parsed.path = Some(PathBuf::from(other));
+            }
+            other => anyhow::bail!("unexpected argument '{}'", other),
+        }
+    }
+    Ok(parsed)
+}
+
+/// A single file is a history of one migration.
+fn load_path(path: &std::path::Path) -> Result<Vec<migrations::Migration>> {
+    if path.is_dir() {
+        migrations::load_dir(path)
+    } else {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(vec![migrations::Migration::load(path, name)?])
+    }
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -50,11 +107,15 @@ fn run_app<B: ratatui::backend::Backend>(
     loop {
         terminal.draw(|f| ui(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.code == KeyCode::Char('q') && app.mode == app::AppMode::DiagramView {
-                return Ok(());
+        match event::read()? {
+            Event::Key(key) => {
+                if key.code == KeyCode::Char('q') && app.mode == app::AppMode::DiagramView {
+                    return Ok(());
+                }
+                app.handle_key(key);
             }
-            app.handle_key(key);
+            Event::Mouse(mouse) => app.handle_mouse(mouse),
+            _ => {}
         }
 
         if app.should_quit {
@@ -63,13 +124,30 @@ fn run_app<B: ratatui::backend::Backend>(
     }
 }
 
-fn ui(f: &mut ratatui::Frame, app: &App) {
+/// The diagram with everything the app highlights on it, sized to `area`.
+fn render_diagram(f: &mut ratatui::Frame, app: &mut App, area: ratatui::layout::Rect) {
+    app.camera.set_viewport(area);
+    let renderer = render::DiagramRenderer::new().with_notation(app.notation).with_camera(app.camera)
+        .with_minimap(app.show_minimap)
+        .with_highlighted_relationship(app.selected_relationship)
+        .with_neighborhood_hops(app.highlight_hops)
+        .with_stubs(app.focus.as_ref().map(|focus| focus.stubs.clone()).unwrap_or_default())
+        .with_selected_column(app.selected_column.clone());
+    renderer.render(f, &app.schema, area, &app.selected_entity, &mut app.route_cache, &mut app.hit_map);
+    if app.show_history {
+        // Stacked above the minimap, which shares the right-hand side
+        let panel_area = if app.show_minimap { render::above_minimap(area) } else { area };
+        render::render_history(f, &app.history, panel_area);
+    }
+}
+
+fn ui(f: &mut ratatui::Frame, app: &mut App) {
     use ratatui::{
         layout::{Constraint, Direction, Layout},
         style::{Color, Style},
         widgets::{Block, Borders, Paragraph},
     };
-    use render::{DiagramRenderer, render_help_screen, render_sql_editor_with_vim, render_entity_creator};
+    use render::{render_help_screen, render_sql_editor_with_vim, render_entity_creator, render_entity_detail, render_search};
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -82,11 +160,10 @@ fn ui(f: &mut ratatui::Frame, app: &App) {
 
     match app.mode {
         app::AppMode::DiagramView => {
-            let renderer = DiagramRenderer::new(800, 600);
-            renderer.render(f, &app.schema, chunks[0], &app.selected_entity);
+            render_diagram(f, app, chunks[0]);
         }
         app::AppMode::SqlEditor => {
-            render_sql_editor_with_vim(f, &app.sql_content, app.vim_mode, app.cursor_position, chunks[0]);
+            render_sql_editor_with_vim(f, &app.sql_content, app.vim_mode, app.cursor_position, &app.parse_errors, chunks[0]);
         }
         app::AppMode::EntityCreator => {
             render_entity_creator(f, &app.entity_creator_buffer, chunks[0]);
@@ -94,9 +171,20 @@ fn ui(f: &mut ratatui::Frame, app: &App) {
         app::AppMode::Help => {
             render_help_screen(f, chunks[0]);
         }
+        app::AppMode::EntityDetail => {
+            render_diagram(f, app, chunks[0]);
+            if let Some(entity) = app.selected_entity.as_ref().and_then(|name| app.schema.entities.get(name)) {
+                render_entity_detail(f, entity, chunks[0]);
+            }
+        }
+        app::AppMode::Search => {
+            render_diagram(f, app, chunks[0]);
+            render_search(f, &app.search_query, &app.search_matches, app.search_cursor, chunks[0]);
+        }
     }
 
     let mode_text = match app.mode {
+        app::AppMode::DiagramView if app.focus.is_some() => "Diagram (focus)",
         app::AppMode::DiagramView => "Diagram",
         app::AppMode::SqlEditor => {
             match app.vim_mode {
@@ -106,26 +194,40 @@ fn ui(f: &mut ratatui::Frame, app: &App) {
         }, 
         app::AppMode::Help => "Help",
         app::AppMode::EntityCreator => "New Entity",
+        app::AppMode::EntityDetail => "Entity Detail",
+        app::AppMode::Search => "Search",
     };
 
-    let status_message = if app.schema.entities.len() > 0 {
+    let status_message = if !app.schema.entities.is_empty() {
         format!(
-            "Mode: {} | Entities: {} | Relationships: {} | Sample E-commerce Schema Loaded | Press '?' for help | Press 'q' to quit",
+            "Mode: {} | Dialect: {} | Entities: {} | Relationships: {} | {} | Press '?' for help | Press 'q' to quit",
             mode_text,
+            app.active_dialect(),
             app.schema.entities.len(),
-            app.schema.relationships.len()
+            app.schema.relationships.len(),
+            app.source_label()
         )
     } else {
         format!(
-            "Mode: {} | Entities: {} | Press '?' for help | Press 'q' to quit",
+            "Mode: {} | Entities: {} | {} | Press '?' for help | Press 'q' to quit",
             mode_text,
-            app.schema.entities.len()
+            app.schema.entities.len(),
+            app.source_label()
         )
     };
 
+    // A parse error takes over the status bar until the SQL is fixed
+    let (status_message, status_color) = match app.parse_errors.first() {
+        Some(error) if app.parse_errors.len() > 1 => {
+            (format!("Parse error in {} (+{} more)", error, app.parse_errors.len() - 1), Color::Red)
+        }
+        Some(error) => (format!("Parse error in {}", error), Color::Red),
+        None => (status_message, Color::Yellow),
+    };
+
     let status_bar = Paragraph::new(status_message)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Yellow));
+        .style(Style::default().fg(status_color));
     
     f.render_widget(status_bar, chunks[1]);
 }
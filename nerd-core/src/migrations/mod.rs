@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// One migration file, in the order it would be applied.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    /// Display name, e.g. `0002_add_orders.sql` or `2023-01-01-000000_init`
+    pub name: String,
+    pub sql: String,
+    key: MigrationKey,
+}
+
+/// Sort key following the naming conventions of common migration tools:
+///
+/// - numbered files: `0001_init.sql`
+/// - sqlx: `20240101120000_init.sql`, `20240101120000_init.up.sql`
+/// - Flyway: `V1__init.sql`, `V1_1__fix.sql`, repeatable `R__views.sql` last
+/// - diesel: `2024-01-01-120000_init/up.sql`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct MigrationKey {
+    repeatable: bool,
+    version: Vec<u64>,
+    name: String,
+}
+
+impl Migration {
+    pub fn load(path: &Path, name: String) -> Result<Self> {
+        let sql = fs::read_to_string(path).with_context(|| format!("reading migration {}", path.display()))?;
+        let key = MigrationKey::from_name(&name);
+        Ok(Self { name, sql, key })
+    }
+}
+
+impl MigrationKey {
+    fn from_name(name: &str) -> Self {
+        // Flyway: V<version>__<description>
+        if let Some(rest) = name.strip_prefix('V').or_else(|| name.strip_prefix('v'))
+            && let Some((version, _)) = rest.split_once("__")
+            && version.starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Self { repeatable: false, version: version_numbers(version), name: name.to_string() };
+        }
+        if name.starts_with("R__") {
+            return Self { repeatable: true, version: Vec::new(), name: name.to_string() };
+        }
+
+        // Numbered, sqlx timestamps and diesel dates: the leading digits and separators
+        let prefix: String = name
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '-' || *c == '.')
+            .collect();
+        Self { repeatable: false, version: version_numbers(&prefix), name: name.to_string() }
+    }
+}
+
+/// `1_2`, `1.2` and `2024-01-01-000000` all become their numeric parts.
+fn version_numbers(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().unwrap_or(u64::MAX))
+        .collect()
+}
+
+/// Load every migration in `dir`, sorted in application order. Down/undo
+/// migrations are skipped.
+pub fn load_dir(dir: &Path) -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    for entry in fs::read_dir(dir).with_context(|| format!("reading migration directory {}", dir.display()))? {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+
+        if path.is_dir() {
+            // diesel keeps each migration in its own directory
+            let up = path.join("up.sql");
+            if up.is_file() {
+                migrations.push(Migration::load(&up, file_name)?);
+            }
+            continue;
+        }
+
+        let is_sql = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("sql"));
+        // sqlx `.down.sql` and Flyway undo `U1__x.sql`
+        let is_down = file_name.ends_with(".down.sql")
+            || (file_name.starts_with('U') && file_name[1..].starts_with(|c: char| c.is_ascii_digit()));
+        if is_sql && !is_down {
+            migrations.push(Migration::load(&path, file_name)?);
+        }
+    }
+
+    if migrations.is_empty() {
+        anyhow::bail!("no .sql migrations found in {}", dir.display());
+    }
+
+    migrations.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(migrations)
+}
+
+/// Concatenate the first `count` migrations into one script, each headed by
+/// its file name.
+pub fn script(migrations: &[Migration], count: usize) -> String {
+    migrations
+        .iter()
+        .take(count)
+        .map(|m| format!("-- {}\n{}", m.name, m.sql.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut keys: Vec<MigrationKey> = names.iter().map(|n| MigrationKey::from_name(n)).collect();
+        keys.sort();
+        keys.into_iter().map(|k| k.name).collect()
+    }
+
+    #[test]
+    fn test_numbered_migrations_sort_numerically() {
+        assert_eq!(
+            sorted(&["0010_later.sql", "0002_orders.sql", "0001_init.sql"]),
+            vec!["0001_init.sql", "0002_orders.sql", "0010_later.sql"]
+        );
+        assert_eq!(
+            sorted(&["20240301000000_b.up.sql", "20240101000000_a.up.sql"]),
+            vec!["20240101000000_a.up.sql", "20240301000000_b.up.sql"]
+        );
+        assert_eq!(
+            sorted(&["2024-01-02-000000_b", "2023-12-31-235959_a"]),
+            vec!["2023-12-31-235959_a", "2024-01-02-000000_b"]
+        );
+    }
+
+    #[test]
+    fn test_flyway_versions_and_repeatables() {
+        assert_eq!(
+            sorted(&["R__views.sql", "V10__c.sql", "V1_1__b.sql", "V2__c.sql", "V1__a.sql"]),
+            vec!["V1__a.sql", "V1_1__b.sql", "V2__c.sql", "V10__c.sql", "R__views.sql"]
+        );
+    }
+
+    #[test]
+    fn test_load_dir_replays_in_order() {
+        let dir = std::env::temp_dir().join(format!("nerd-migrations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("0002_orders.sql"), "CREATE TABLE orders (id INT PRIMARY KEY);").unwrap();
+        fs::write(dir.join("0001_init.sql"), "CREATE TABLE users (id INT PRIMARY KEY);").unwrap();
+        fs::write(dir.join("0002_orders.down.sql"), "DROP TABLE orders;").unwrap();
+        fs::write(dir.join("notes.txt"), "not sql").unwrap();
+
+        let migrations = load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = migrations.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["0001_init.sql", "0002_orders.sql"]);
+        assert_eq!(
+            script(&migrations, 1),
+            "-- 0001_init.sql\nCREATE TABLE users (id INT PRIMARY KEY);"
+        );
+    }
+}
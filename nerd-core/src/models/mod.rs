@@ -1,15 +1,86 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Entity {
     pub name: String,
     pub columns: Vec<Column>,
     pub position: Position,
     pub dimensions: Dimensions,
+    #[serde(default)]
+    pub unique_constraints: Vec<UniqueConstraint>,
+    #[serde(default)]
+    pub indexes: Vec<Index>,
+    #[serde(default)]
+    pub check_constraints: Vec<CheckConstraint>,
+    #[serde(default)]
+    pub options: TableOptions,
+    /// Placed by hand: layouts keep it where it is.
+    #[serde(default)]
+    pub pinned: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Table-level storage options that only some dialects understand.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TableOptions {
+    /// MySQL `ENGINE=InnoDB`
+    pub engine: Option<String>,
+    /// MySQL `DEFAULT CHARSET=utf8mb4`
+    pub charset: Option<String>,
+    pub collation: Option<String>,
+    /// SQLite `WITHOUT ROWID`
+    pub without_rowid: bool,
+}
+
+impl Entity {
+    /// True when the column alone is guaranteed unique, either by a
+    /// single-column UNIQUE constraint or a full (non-partial) unique index.
+    pub fn is_unique_column(&self, column: &str) -> bool {
+        self.unique_constraints
+            .iter()
+            .any(|unique| unique.columns.len() == 1 && unique.columns[0] == column)
+            || self.indexes.iter().any(|index| {
+                index.unique
+                    && index.predicate.is_none()
+                    && index.key_parts.len() == 1
+                    && index.key_parts[0].split_whitespace().next() == Some(column)
+            })
+    }
+
+    /// True when the columns, in any order, are the primary key or carry a
+    /// unique constraint or full unique index of their own.
+    pub fn is_unique_key(&self, columns: &[&str]) -> bool {
+        let same_set = |other: Vec<&str>| {
+            other.len() == columns.len() && columns.iter().all(|c| other.contains(c))
+        };
+
+        !columns.is_empty()
+            && (same_set(self.primary_key_columns().collect())
+                || self.unique_constraints.iter().any(|u| same_set(u.columns.iter().map(String::as_str).collect()))
+                || self.indexes.iter().any(|index| {
+                    index.unique
+                        && index.predicate.is_none()
+                        && same_set(index.key_parts.iter().filter_map(|p| p.split_whitespace().next()).collect())
+                }))
+    }
+
+    pub fn primary_key_columns(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().filter(|c| c.is_primary_key).map(|c| c.name.as_str())
+    }
+
+    pub fn is_indexed_column(&self, column: &str) -> bool {
+        // Key parts may carry ASC/DESC after the column name
+        self.indexes.iter().any(|index| {
+            index
+                .key_parts
+                .iter()
+                .any(|part| part.split_whitespace().next() == Some(column))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
     pub data_type: String,
@@ -17,21 +88,144 @@ pub struct Column {
     pub is_primary_key: bool,
     pub is_foreign_key: bool,
     pub references: Option<ForeignKeyReference>,
+    /// The `DEFAULT` expression as SQL text, e.g. `CURRENT_TIMESTAMP`.
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub generated: Option<GeneratedColumn>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// MySQL `ON UPDATE <expr>`, e.g. `CURRENT_TIMESTAMP`.
+    #[serde(default)]
+    pub on_update: Option<String>,
+}
+
+/// How the database fills in a column's value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GeneratedColumn {
+    /// `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY [ (<sequence options>) ]`
+    Identity {
+        always: bool,
+        sequence_options: Option<String>,
+    },
+    /// `GENERATED ALWAYS AS (<expr>) [ STORED ]`
+    Computed { expression: String, stored: bool },
+}
+
+impl fmt::Display for GeneratedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratedColumn::Identity { always, sequence_options } => {
+                write!(f, "GENERATED {} AS IDENTITY", if *always { "ALWAYS" } else { "BY DEFAULT" })?;
+                if let Some(options) = sequence_options {
+                    write!(f, " ({})", options)?;
+                }
+                Ok(())
+            }
+            GeneratedColumn::Computed { expression, stored } => {
+                write!(f, "GENERATED ALWAYS AS ({})", expression)?;
+                if *stored {
+                    write!(f, " STORED")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UniqueConstraint {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+}
+
+/// A named index. Key parts are kept as SQL text so that expression indexes
+/// such as `lower(email)` survive the round trip.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Index {
+    pub name: String,
+    pub key_parts: Vec<String>,
+    pub unique: bool,
+    /// The `WHERE` clause of a partial index.
+    pub predicate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckConstraint {
+    pub name: Option<String>,
+    pub expression: String,
+}
+
+/// One local column of a foreign key and the referenced column it maps to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnPair {
+    pub from_column: String,
+    pub to_column: String,
 }
 
+impl ColumnPair {
+    pub fn new(from_column: impl Into<String>, to_column: impl Into<String>) -> Self {
+        Self {
+            from_column: from_column.into(),
+            to_column: to_column.into(),
+        }
+    }
+}
+
+/// The foreign key a column takes part in. For composite keys every member
+/// column carries the full, ordered list of pairs.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ForeignKeyReference {
     pub table: String,
-    pub column: String,
+    pub column_pairs: Vec<ColumnPair>,
 }
 
+/// A foreign key between two tables. Composite keys are a single
+/// relationship with several ordered column pairs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Relationship {
+    /// Constraint name, when the DDL gave one
+    #[serde(default)]
+    pub name: Option<String>,
     pub from_table: String,
-    pub from_column: String,
     pub to_table: String,
-    pub to_column: String,
+    pub column_pairs: Vec<ColumnPair>,
     pub relationship_type: RelationshipType,
+    /// The foreign key is nullable, so a row need not have a parent
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Relationship {
+    pub fn local_columns(&self) -> impl Iterator<Item = &str> {
+        self.column_pairs.iter().map(|pair| pair.from_column.as_str())
+    }
+
+    pub fn referenced_columns(&self) -> impl Iterator<Item = &str> {
+        self.column_pairs.iter().map(|pair| pair.to_column.as_str())
+    }
+
+    /// Multiplicity written at the (child, parent) ends of the line.
+    pub fn cardinality_labels(&self) -> (&'static str, &'static str) {
+        let child = match self.relationship_type {
+            RelationshipType::OneToOne => "1",
+            // Each edge of a junction table is one half of the M:N, so its
+            // junction end is many like any other child
+            RelationshipType::OneToMany | RelationshipType::ManyToMany => "N",
+        };
+        let parent = if self.optional { "0..1" } else { "1" };
+        (child, parent)
+    }
+
+    /// The declared constraint name, or the one nERD generates for it. A
+    /// schema prefix becomes part of the generated name (`fk_dbo_orders_…`),
+    /// since constraint names are never qualified.
+    pub fn constraint_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let columns = self.local_columns().collect::<Vec<_>>().join("_");
+            format!("fk_{}_{}", self.from_table, columns).replace('.', "_")
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -41,7 +235,7 @@ pub enum RelationshipType {
     ManyToMany,
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -53,17 +247,65 @@ pub struct Dimensions {
     pub height: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Dimensions {
+    /// Box size in cells for an entity as the renderer draws it: a border
+    /// around the title and one `key name type? ◆` line per column.
+    pub fn for_entity(entity: &Entity) -> Self {
+        Self::for_columns(entity, &entity.columns.iter().collect::<Vec<_>>())
+    }
+
+    /// Box size when only some of the entity's columns are listed.
+    pub fn for_columns(entity: &Entity, columns: &[&Column]) -> Self {
+        // Key icon and its space, the space before the type, the nullable
+        // mark and the constraint mark
+        const MARKERS: usize = 3 + 1 + 1 + 2;
+
+        let widest_column = columns
+            .iter()
+            .map(|col| col.name.chars().count() + col.data_type.chars().count() + MARKERS)
+            .max()
+            .unwrap_or(0);
+        // The title has room for the pin marker
+        let content_width = widest_column.max(entity.name.chars().count() + 3);
+
+        Self {
+            width: (content_width + 2).min(u16::MAX as usize) as u16,
+            height: (columns.len().max(1) + 2).min(u16::MAX as usize) as u16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Schema {
-    pub entities: HashMap<String, Entity>,
+    /// Tables in declaration order, so layouts and generated SQL are stable
+    pub entities: IndexMap<String, Entity>,
     pub relationships: Vec<Relationship>,
 }
 
 impl Schema {
     pub fn new() -> Self {
         Self {
-            entities: HashMap::new(),
+            entities: IndexMap::new(),
             relationships: Vec::new(),
         }
     }
+
+    /// The tables `keep` accepts, in declaration order, and the relationships
+    /// between them.
+    pub fn subset(&self, keep: impl Fn(&str) -> bool) -> Schema {
+        Schema {
+            entities: self
+                .entities
+                .iter()
+                .filter(|(name, _)| keep(name))
+                .map(|(name, entity)| (name.clone(), entity.clone()))
+                .collect(),
+            relationships: self
+                .relationships
+                .iter()
+                .filter(|r| keep(&r.from_table) && keep(&r.to_table))
+                .cloned()
+                .collect(),
+        }
+    }
 }
\ No newline at end of file
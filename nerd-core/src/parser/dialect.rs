@@ -0,0 +1,181 @@
+use sqlparser::dialect::{
+    Dialect, GenericDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect, SnowflakeDialect,
+};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqlDialect {
+    #[default]
+    Generic,
+    PostgreSql,
+    MySql,
+    Sqlite,
+    MsSql,
+    Snowflake,
+}
+
+impl SqlDialect {
+    pub fn parser_dialect(&self) -> Box<dyn Dialect> {
+        match self {
+            SqlDialect::Generic => Box::new(GenericDialect {}),
+            SqlDialect::PostgreSql => Box::new(PostgreSqlDialect {}),
+            SqlDialect::MySql => Box::new(MySqlDialect {}),
+            SqlDialect::Sqlite => Box::new(SQLiteDialect {}),
+            SqlDialect::MsSql => Box::new(MsSqlDialect {}),
+            SqlDialect::Snowflake => Box::new(SnowflakeDialect {}),
+        }
+    }
+
+    /// Guess the dialect of a dump from syntax only one of them uses.
+    /// Falls back to `Generic` when nothing stands out.
+    pub fn detect(sql: &str) -> Self {
+        let code = strip_comments_and_strings(sql).to_uppercase();
+        let has_word = |word: &str| {
+            code.match_indices(word).any(|(i, _)| {
+                let before = code[..i].chars().next_back();
+                let after = code[i + word.len()..].chars().next();
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            })
+        };
+
+        // `[dbo].[users]`, but not Postgres array types like `TEXT[]`
+        let has_bracket_identifier = code
+            .match_indices('[')
+            .any(|(i, _)| code[i + 1..].chars().next().is_some_and(|c| c.is_alphabetic() || c == '_'));
+
+        if code.contains('`') || has_word("AUTO_INCREMENT") || code.contains("ENGINE=") || code.contains("ENGINE =") {
+            SqlDialect::MySql
+        } else if has_word("VARIANT") || has_word("TIMESTAMP_NTZ") || has_word("TRANSIENT") {
+            // Before SQLite: Snowflake also spells it `AUTOINCREMENT`
+            SqlDialect::Snowflake
+        } else if code.contains("WITHOUT ROWID") || (has_word("AUTOINCREMENT") && !has_word("START")) {
+            SqlDialect::Sqlite
+        } else if has_word("SERIAL") || has_word("BIGSERIAL") || has_word("SMALLSERIAL") || code.contains("::") {
+            SqlDialect::PostgreSql
+        } else if has_bracket_identifier || has_word("NVARCHAR") || code.contains("IDENTITY(") {
+            SqlDialect::MsSql
+        } else if has_word("AUTOINCREMENT") {
+            SqlDialect::Snowflake
+        } else {
+            SqlDialect::Generic
+        }
+    }
+}
+
+impl fmt::Display for SqlDialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SqlDialect::Generic => "generic",
+            SqlDialect::PostgreSql => "postgres",
+            SqlDialect::MySql => "mysql",
+            SqlDialect::Sqlite => "sqlite",
+            SqlDialect::MsSql => "mssql",
+            SqlDialect::Snowflake => "snowflake",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SqlDialect {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "generic" | "ansi" => Ok(SqlDialect::Generic),
+            "postgres" | "postgresql" | "pg" => Ok(SqlDialect::PostgreSql),
+            "mysql" | "mariadb" => Ok(SqlDialect::MySql),
+            "sqlite" => Ok(SqlDialect::Sqlite),
+            "mssql" | "sqlserver" | "tsql" => Ok(SqlDialect::MsSql),
+            "snowflake" => Ok(SqlDialect::Snowflake),
+            other => Err(anyhow::anyhow!(
+                "unknown dialect '{}', expected one of: generic, postgres, mysql, sqlite, mssql, snowflake",
+                other
+            )),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Blank out `--` / `/* */` comments and single-quoted strings so that
+/// detection only looks at actual syntax.
+fn strip_comments_and_strings(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        result.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                result.push(' ');
+            }
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                result.push_str("''");
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_dialect() {
+        assert_eq!(SqlDialect::detect("CREATE TABLE `users` (id INT AUTO_INCREMENT) ENGINE=InnoDB;"), SqlDialect::MySql);
+        assert_eq!(SqlDialect::detect("CREATE TABLE users (id SERIAL PRIMARY KEY);"), SqlDialect::PostgreSql);
+        assert_eq!(SqlDialect::detect("CREATE TABLE t (a INT PRIMARY KEY) WITHOUT ROWID;"), SqlDialect::Sqlite);
+        assert_eq!(SqlDialect::detect("CREATE TABLE [dbo].[t] (id INT IDENTITY(1,1));"), SqlDialect::MsSql);
+        assert_eq!(SqlDialect::detect("CREATE TABLE t (payload VARIANT);"), SqlDialect::Snowflake);
+        assert_eq!(
+            SqlDialect::detect("CREATE TABLE t (id NUMBER AUTOINCREMENT PRIMARY KEY, v VARIANT);"),
+            SqlDialect::Snowflake
+        );
+        assert_eq!(SqlDialect::detect("CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT);"), SqlDialect::Sqlite);
+        assert_eq!(SqlDialect::detect("CREATE TABLE t (id INT);"), SqlDialect::Generic);
+        assert_eq!(SqlDialect::detect("CREATE TABLE t (engine TEXT, tags TEXT[]);"), SqlDialect::Generic);
+    }
+
+    #[test]
+    fn test_detect_ignores_comments_and_strings() {
+        let sql = "-- exported from MySQL with `mysqldump`\nCREATE TABLE t (note TEXT DEFAULT 'x::y');";
+        assert_eq!(SqlDialect::detect(sql), SqlDialect::Generic);
+    }
+
+    #[test]
+    fn test_dialect_from_str() {
+        assert_eq!("PostgreSQL".parse::<SqlDialect>().unwrap(), SqlDialect::PostgreSql);
+        assert_eq!("sqlserver".parse::<SqlDialect>().unwrap(), SqlDialect::MsSql);
+        assert!("oracle".parse::<SqlDialect>().is_err());
+    }
+}
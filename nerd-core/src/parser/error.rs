@@ -0,0 +1,54 @@
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::Location;
+use std::fmt;
+
+/// A statement that failed to parse, located in the original script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Zero-based index of the statement within the script
+    pub statement_index: usize,
+    /// One-based line of the error
+    pub line: usize,
+    /// One-based column of the error
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// sqlparser appends ` at Line: x, Column: y` to most messages; use that
+    /// position when present, otherwise the start of the statement.
+    pub(super) fn from_parser_error(statement_index: usize, error: ParserError, statement_start: Location) -> Self {
+        let message = match error {
+            ParserError::ParserError(message) | ParserError::TokenizerError(message) => message,
+            ParserError::RecursionLimitExceeded => "statement is nested too deeply".to_string(),
+        };
+
+        let (message, line, column) = match split_location(&message) {
+            Some((text, line, column)) => (text.to_string(), line, column),
+            None => (message, statement_start.line as usize, statement_start.column as usize),
+        };
+
+        Self { statement_index, line, column, message }
+    }
+}
+
+fn split_location(message: &str) -> Option<(&str, usize, usize)> {
+    let at = message.rfind(" at Line: ")?;
+    let (line, column) = message[at + " at Line: ".len()..].split_once(", Column: ")?;
+    Some((&message[..at], line.trim().parse().ok()?, column.trim().parse().ok()?))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "statement {} (line {}, column {}): {}",
+            self.statement_index + 1,
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,148 +1,626 @@
-use crate::models::{Column, Entity, Schema, Position, Dimensions, Relationship, RelationshipType, ForeignKeyReference};
+use crate::models::{CheckConstraint, Column, ColumnPair, Entity, GeneratedColumn, Index, Schema, Position, Dimensions, Relationship, RelationshipType, ForeignKeyReference, TableOptions, UniqueConstraint};
 use anyhow::Result;
-use sqlparser::ast::{ColumnDef, ColumnOption, DataType, Statement, TableConstraint};
-use sqlparser::dialect::GenericDialect;
+use sqlparser::ast::{
+    ColumnDef, ColumnOption, CommentObject, CreateIndex, CreateTable, DataType, Expr, GeneratedAs, GeneratedExpressionMode,
+    Ident, IdentityPropertyFormatKind, IdentityPropertyKind, ObjectName, ObjectType, OrderByExpr, SequenceOptions, Statement,
+    TableConstraint,
+};
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Location, Token, TokenWithLocation, Tokenizer};
 use sqlparser::parser::Parser;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+
+mod dialect;
+mod error;
+mod prescan;
+mod replay;
+
+pub use dialect::SqlDialect;
+pub use error::ParseError;
+use prescan::{prescan, Prescan, Step};
+use replay::{drop_foreign_key, drop_table, rename_table};
+
+/// A statement of the script: one sqlparser read, or one of the MySQL
+/// statements the pre-scan picks out.
+enum ScriptStatement {
+    Parsed(Box<Statement>),
+    RenameTables(Vec<(String, String)>),
+    DropForeignKeys { table: String, names: Vec<String> },
+}
+
+/// A statement with its index and where it starts, so what goes wrong
+/// replaying it is reported like a parse error.
+struct Located {
+    index: usize,
+    start: Location,
+    statement: ScriptStatement,
+}
 
 pub struct SqlParser {
-    dialect: GenericDialect,
+    dialect: SqlDialect,
 }
 
 impl SqlParser {
-    pub fn new() -> Self {
-        Self {
-            dialect: GenericDialect {},
-        }
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self { dialect }
     }
 
+    /// Parse a script, failing with the first statement that does not parse.
+    /// The app itself always parses leniently.
     pub fn parse_sql(&self, sql: &str) -> Result<Schema> {
-        let statements = Parser::parse_sql(&self.dialect, sql)?;
-        let mut entities = HashMap::new();
+        let (schema, errors) = self.parse_sql_lenient(sql);
+        match errors.into_iter().next() {
+            Some(error) => Err(error.into()),
+            None => Ok(schema),
+        }
+    }
+
+    /// Replay a script in order, so that a migration history ends up as the
+    /// schema it produces. Statements that fail to parse, and changes the
+    /// replay cannot follow, are skipped and returned alongside the schema
+    /// built from the rest.
+    pub fn parse_sql_lenient(&self, sql: &str) -> (Schema, Vec<ParseError>) {
+        let (statements, mut errors, _) = self.parse_statements(sql);
+        let mut entities = IndexMap::new();
         let mut relationships = Vec::new();
+        let mut pending_indexes: Vec<(String, Index)> = Vec::new();
         
-        for statement in statements {
+        for Located { index, start, statement } in statements {
+            let mut report = |message: String| {
+                errors.push(ParseError {
+                    statement_index: index,
+                    line: start.line as usize,
+                    column: start.column as usize,
+                    message,
+                });
+            };
+            let statement = match statement {
+                ScriptStatement::Parsed(statement) => *statement,
+                ScriptStatement::RenameTables(renames) => {
+                    for (old_name, new_name) in &renames {
+                        if let Err(message) = rename_table(old_name, new_name, &mut entities, &mut relationships) {
+                            report(message);
+                        }
+                    }
+                    continue;
+                }
+                ScriptStatement::DropForeignKeys { table, names } => {
+                    for name in &names {
+                        if let Err(message) = drop_foreign_key(&table, name, &mut entities, &mut relationships) {
+                            report(message);
+                        }
+                    }
+                    continue;
+                }
+            };
+
             match statement {
                 Statement::CreateTable(create_table) => {
-                    let table_name = create_table.name.to_string();
-                    let (entity, table_relationships) = self.parse_table(&table_name, &create_table.columns, &create_table.constraints)?;
+                    let table_name = object_name(&create_table.name);
+                    let (mut entity, table_relationships) = self.parse_table(&table_name, &create_table.columns, &create_table.constraints);
+                    entity.options = self.parse_table_options(&create_table);
+                    
+                    // Indexes may be declared before their table
+                    let (own_indexes, others) = pending_indexes.into_iter().partition(|(table, _)| *table == table_name);
+                    pending_indexes = others;
+                    entity.indexes.extend(own_indexes.into_iter().map(|(_, index)| index));
+                    
                     entities.insert(table_name, entity);
                     relationships.extend(table_relationships);
                 }
+                Statement::CreateIndex(create_index) => {
+                    let table_name = object_name(&create_index.table_name);
+                    let index = self.parse_index(&create_index);
+                    match entities.get_mut(&table_name) {
+                        Some(entity) => entity.indexes.push(index),
+                        None => pending_indexes.push((table_name, index)),
+                    }
+                }
+                // `COMMENT ON COLUMN table.column IS '...'`
+                Statement::Comment { object_type: CommentObject::Column, object_name: column_path, comment, .. } => {
+                    if let [table_parts @ .., column_name] = column_path.0.as_slice() {
+                        let table_name = object_name(&ObjectName(table_parts.to_vec()));
+                        if let Some(column) = entities.get_mut(&table_name)
+                            .and_then(|entity| entity.columns.iter_mut().find(|c| c.name == column_name.value))
+                        {
+                            column.comment = comment;
+                        }
+                    }
+                }
+                Statement::AlterTable { name, operations, .. } => {
+                    let mut table_name = object_name(&name);
+                    for operation in &operations {
+                        if let Err(message) = self.apply_alter_operation(&mut table_name, operation, &mut entities, &mut relationships) {
+                            report(message);
+                        }
+                    }
+                }
+                Statement::Drop { object_type: ObjectType::Table, names, .. } => {
+                    for name in &names {
+                        drop_table(&object_name(name), &mut entities, &mut relationships);
+                    }
+                }
+                Statement::Drop { object_type: ObjectType::Index, names, .. } => {
+                    for name in &names {
+                        let index_name = name.0.last().map(ident_name).unwrap_or_default();
+                        for entity in entities.values_mut() {
+                            entity.indexes.retain(|index| index.name != index_name);
+                        }
+                    }
+                }
                 _ => continue,
             }
         }
         
-        Ok(Schema {
-            entities,
-            relationships,
-        })
+        self.resolve_implicit_references(&mut entities, &mut relationships);
+        self.infer_cardinality(&entities, &mut relationships);
+
+        // Size boxes after ALTERs have settled the final columns
+        for entity in entities.values_mut() {
+            entity.dimensions = Dimensions::for_entity(entity);
+        }
+        
+        // Replay errors come after the parse errors; keep them in script order
+        errors.sort_by_key(|error| error.statement_index);
+        (Schema { entities, relationships }, errors)
     }
 
-    fn parse_table(&self, table_name: &str, columns: &[ColumnDef], constraints: &Vec<TableConstraint>) -> Result<(Entity, Vec<Relationship>)> {
-        let mut parsed_columns = Vec::new();
+    /// Split the script at `;` and parse each statement on its own, so one
+    /// broken statement does not hide the others. Also returns how many
+    /// statements were found.
+    fn parse_statements(&self, sql: &str) -> (Vec<Located>, Vec<ParseError>, usize) {
+        let dialect = self.dialect.parser_dialect();
+        let tokens = match Tokenizer::new(dialect.as_ref(), sql).with_unescape(true).tokenize_with_location() {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                // Keep the statements before the one the tokenizer gave up in
+                let offset = byte_offset(sql, error.location);
+                let prefix_end = sql[..offset].rfind(';').map_or(0, |i| i + 1);
+                let (mut statements, mut errors, count) = self.parse_statements(&sql[..prefix_end]);
+                errors.push(ParseError {
+                    statement_index: count,
+                    line: error.location.line as usize,
+                    column: error.location.column as usize,
+                    message: error.message,
+                });
+                let Some(resume) = sql[offset..].find(';').map(|i| offset + i + 1) else {
+                    return (statements, errors, count + 1);
+                };
+
+                // ...and the ones after it, moved to where they sit in the script
+                let base = location_at(sql, resume);
+                let (rest, rest_errors, rest_count) = self.parse_statements(&sql[resume..]);
+                let skipped = count + 1;
+                statements.extend(rest.into_iter().map(|located| Located {
+                    index: located.index + skipped,
+                    start: shift_location(located.start, base),
+                    statement: located.statement,
+                }));
+                errors.extend(rest_errors.into_iter().map(|error| {
+                    let at = shift_location(Location { line: error.line as u64, column: error.column as u64 }, base);
+                    ParseError {
+                        statement_index: error.statement_index + skipped,
+                        line: at.line as usize,
+                        column: at.column as usize,
+                        ..error
+                    }
+                }));
+                return (statements, errors, skipped + rest_count);
+            }
+        };
+
+        let mut groups: Vec<Vec<TokenWithLocation>> = vec![Vec::new()];
+        for token in tokens {
+            if token.token == Token::SemiColon {
+                groups.push(Vec::new());
+            } else if let Some(group) = groups.last_mut() {
+                group.push(token);
+            }
+        }
+        groups.retain(|group| group.iter().any(|t| !matches!(t.token, Token::Whitespace(_))));
+
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+        let count = groups.len();
+        for (statement_index, group) in groups.into_iter().enumerate() {
+            let start = group.iter()
+                .find(|t| !matches!(t.token, Token::Whitespace(_)))
+                .map(|t| t.location)
+                .unwrap_or(Location { line: 1, column: 1 });
+            let located = |statement| Located { index: statement_index, start, statement };
+            let steps = match prescan(group) {
+                Prescan::Parse(tokens) => vec![Step::Parse(tokens)],
+                Prescan::RenameTables(renames) => {
+                    statements.push(located(ScriptStatement::RenameTables(renames)));
+                    continue;
+                }
+                Prescan::DropForeignKeys(steps) => steps,
+                Prescan::Invalid(message) => {
+                    errors.push(ParseError {
+                        statement_index,
+                        line: start.line as usize,
+                        column: start.column as usize,
+                        message,
+                    });
+                    continue;
+                }
+            };
+            for step in steps {
+                let tokens = match step {
+                    Step::Parse(tokens) => tokens,
+                    Step::DropForeignKeys { table, names } => {
+                        statements.push(located(ScriptStatement::DropForeignKeys { table, names }));
+                        continue;
+                    }
+                };
+                match Parser::new(dialect.as_ref()).with_tokens_with_locations(tokens).parse_statements() {
+                    Ok(parsed) => statements.extend(parsed.into_iter().map(|s| located(ScriptStatement::Parsed(Box::new(s))))),
+                    Err(error) => errors.push(ParseError::from_parser_error(statement_index, error, start)),
+                }
+            }
+        }
+
+        (statements, errors, count)
+    }
+
+    fn parse_table(&self, table_name: &str, columns: &[ColumnDef], constraints: &Vec<TableConstraint>) -> (Entity, Vec<Relationship>) {
+        let mut entity = Entity {
+            name: table_name.to_string(),
+            columns: Vec::new(),
+            position: Position::default(),
+            dimensions: Dimensions::default(),
+            unique_constraints: Vec::new(),
+            indexes: Vec::new(),
+            check_constraints: Vec::new(),
+            options: TableOptions::default(),
+            pinned: false,
+        };
         let mut relationships = Vec::new();
         
         // Parse columns
         for column_def in columns {
-            let column_name = column_def.name.to_string();
-            let data_type = self.format_data_type(&column_def.data_type);
-            let mut nullable = true;
-            let mut is_primary_key = false;
-            let mut is_foreign_key = false;
-            let mut references = None;
-            
-            for option_def in &column_def.options {
-                match &option_def.option {
-                    ColumnOption::NotNull => nullable = false,
-                    ColumnOption::Unique { is_primary, .. } if *is_primary => {
-                        is_primary_key = true;
-                        nullable = false;
-                    }
-                    ColumnOption::ForeignKey { foreign_table, referred_columns, .. } => {
-                        is_foreign_key = true;
-                        let ref_table = foreign_table.to_string();
-                        let ref_column = referred_columns.first()
-                            .map(|c| c.to_string())
-                            .unwrap_or_else(|| "id".to_string());
-                        
-                        references = Some(ForeignKeyReference {
-                            table: ref_table.clone(),
-                            column: ref_column.clone(),
-                        });
-                        
-                        relationships.push(Relationship {
-                            from_table: table_name.to_string(),
-                            from_column: column_name.clone(),
-                            to_table: ref_table,
-                            to_column: ref_column,
-                            relationship_type: RelationshipType::OneToMany,
-                        });
-                    }
-                    _ => {}
+            let column = self.parse_column(&mut entity, column_def, &mut relationships);
+            entity.columns.push(column);
+        }
+        
+        // Parse table-level constraints
+        for constraint in constraints {
+            self.apply_table_constraint(&mut entity, constraint, &mut relationships);
+        }
+        
+        (entity, relationships)
+    }
+
+    /// Parse one column definition. Inline constraints are added to `entity`
+    /// and `relationships`; the column itself is returned for the caller to place.
+    fn parse_column(&self, entity: &mut Entity, column_def: &ColumnDef, relationships: &mut Vec<Relationship>) -> Column {
+        let column_name = ident_name(&column_def.name);
+        let mut data_type = self.format_data_type(&column_def.data_type);
+        let mut nullable = true;
+        let mut is_primary_key = false;
+        let mut is_foreign_key = false;
+        let mut references = None;
+        let mut default = None;
+        let mut generated = None;
+        let mut comment = None;
+        let mut on_update = None;
+
+        // Postgres SERIAL types are an integer with an implicit sequence
+        if let Some(base_type) = self.serial_base_type(&column_def.data_type) {
+            data_type = base_type.to_string();
+            nullable = false;
+            generated = Some(GeneratedColumn::Identity { always: false, sequence_options: None });
+        }
+        
+        for option_def in &column_def.options {
+            match &option_def.option {
+                ColumnOption::NotNull => nullable = false,
+                ColumnOption::Unique { is_primary, .. } if *is_primary => {
+                    is_primary_key = true;
+                    nullable = false;
+                }
+                ColumnOption::Unique { .. } => {
+                    entity.unique_constraints.push(UniqueConstraint {
+                        name: option_def.name.as_ref().map(ident_name),
+                        columns: vec![column_name.clone()],
+                    });
+                }
+                ColumnOption::Default(expr) => default = Some(expr.to_string()),
+                ColumnOption::Comment(text) => comment = Some(text.clone()),
+                ColumnOption::OnUpdate(expr) => on_update = Some(expr.to_string()),
+                // MySQL AUTO_INCREMENT / SQLite AUTOINCREMENT
+                ColumnOption::DialectSpecific(tokens) if self.is_auto_increment(tokens) => {
+                    generated = Some(GeneratedColumn::Identity { always: false, sequence_options: None });
+                }
+                // SQL Server IDENTITY(seed, increment) / Snowflake AUTOINCREMENT
+                ColumnOption::Identity(kind) => {
+                    generated = Some(self.parse_identity_property(kind));
+                }
+                ColumnOption::Generated { generated_as, sequence_options, generation_expr, generation_expr_mode, .. } => {
+                    generated = Some(self.parse_generated(generated_as, sequence_options, generation_expr, generation_expr_mode));
+                }
+                ColumnOption::Check(expr) => {
+                    entity.check_constraints.push(CheckConstraint {
+                        name: option_def.name.as_ref().map(ident_name),
+                        expression: expr.to_string(),
+                    });
+                }
+                ColumnOption::ForeignKey { foreign_table, referred_columns, .. } => {
+                    is_foreign_key = true;
+                    let ref_table = object_name(foreign_table);
+                    let ref_column = referred_columns.first()
+                        .map(ident_name)
+                        .unwrap_or_default();
+                    let column_pairs = vec![ColumnPair::new(column_name.clone(), ref_column)];
+                    
+                    references = Some(ForeignKeyReference {
+                        table: ref_table.clone(),
+                        column_pairs: column_pairs.clone(),
+                    });
+                    
+                    relationships.push(Relationship {
+                        name: option_def.name.as_ref().map(ident_name),
+                        from_table: entity.name.clone(),
+                        to_table: ref_table,
+                        column_pairs,
+                        relationship_type: RelationshipType::OneToMany,
+                        optional: false,
+                    });
                 }
+                _ => {}
             }
-            
-            parsed_columns.push(Column {
-                name: column_name,
-                data_type,
-                nullable,
-                is_primary_key,
-                is_foreign_key,
-                references,
-            });
         }
         
-        // Parse table-level foreign key constraints
-        for constraint in constraints {
-            match constraint {
-                TableConstraint::ForeignKey { columns: fk_columns, foreign_table, referred_columns, .. } => {
-                    for fk_col in fk_columns {
-                        let fk_col_name = fk_col.to_string();
-                        let ref_table = foreign_table.to_string();
-                        let ref_column = referred_columns.first()
-                            .map(|c| c.to_string())
-                            .unwrap_or_else(|| "id".to_string());
-                        
-                        // Mark the column as foreign key
-                        if let Some(column) = parsed_columns.iter_mut().find(|c| c.name == fk_col_name) {
-                            column.is_foreign_key = true;
-                            column.references = Some(ForeignKeyReference {
-                                table: ref_table.clone(),
-                                column: ref_column.clone(),
-                            });
-                        }
-                        
-                        relationships.push(Relationship {
-                            from_table: table_name.to_string(),
-                            from_column: fk_col_name,
-                            to_table: ref_table,
-                            to_column: ref_column,
-                            relationship_type: RelationshipType::OneToMany,
+        Column {
+            name: column_name,
+            data_type,
+            nullable,
+            is_primary_key,
+            is_foreign_key,
+            references,
+            default,
+            generated,
+            comment,
+            on_update,
+        }
+    }
+
+    /// Apply a table-level constraint, from `CREATE TABLE` or `ALTER TABLE ADD`.
+    fn apply_table_constraint(&self, entity: &mut Entity, constraint: &TableConstraint, relationships: &mut Vec<Relationship>) {
+        match constraint {
+            TableConstraint::ForeignKey { name, columns: fk_columns, foreign_table, referred_columns, .. } => {
+                let ref_table = object_name(foreign_table);
+                let column_pairs = self.pair_foreign_key_columns(fk_columns, referred_columns);
+                
+                // Every member column knows about the whole key
+                for pair in &column_pairs {
+                    if let Some(column) = entity.columns.iter_mut().find(|c| c.name == pair.from_column) {
+                        column.is_foreign_key = true;
+                        column.references = Some(ForeignKeyReference {
+                            table: ref_table.clone(),
+                            column_pairs: column_pairs.clone(),
                         });
                     }
                 }
-                TableConstraint::PrimaryKey { columns: pk_columns, .. } => {
-                    for pk_col in pk_columns {
-                        let pk_col_name = pk_col.to_string();
-                        if let Some(column) = parsed_columns.iter_mut().find(|c| c.name == pk_col_name) {
-                            column.is_primary_key = true;
-                            column.nullable = false;
-                        }
+                
+                relationships.push(Relationship {
+                    name: name.as_ref().map(ident_name),
+                    from_table: entity.name.clone(),
+                    to_table: ref_table,
+                    column_pairs,
+                    relationship_type: RelationshipType::OneToMany,
+                    optional: false,
+                });
+            }
+            TableConstraint::PrimaryKey { columns: pk_columns, .. } => {
+                for pk_col in pk_columns {
+                    let pk_col_name = ident_name(pk_col);
+                    if let Some(column) = entity.columns.iter_mut().find(|c| c.name == pk_col_name) {
+                        column.is_primary_key = true;
+                        column.nullable = false;
                     }
                 }
-                _ => {}
             }
+            TableConstraint::Unique { name, index_name, columns: unique_columns, .. } => {
+                entity.unique_constraints.push(UniqueConstraint {
+                    name: name.as_ref().or(index_name.as_ref()).map(ident_name),
+                    columns: unique_columns.iter().map(ident_name).collect(),
+                });
+            }
+            TableConstraint::Check { name, expr } => {
+                entity.check_constraints.push(CheckConstraint {
+                    name: name.as_ref().map(ident_name),
+                    expression: expr.to_string(),
+                });
+            }
+            TableConstraint::Index { name, columns: index_columns, .. } => {
+                let key_parts: Vec<String> = index_columns.iter().map(ident_name).collect();
+                entity.indexes.push(Index {
+                    name: name.as_ref()
+                        .map(ident_name)
+                        .unwrap_or_else(|| self.default_index_name(&entity.name, &key_parts)),
+                    key_parts,
+                    unique: false,
+                    predicate: None,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn parse_generated(
+        &self,
+        generated_as: &GeneratedAs,
+        sequence_options: &Option<Vec<SequenceOptions>>,
+        generation_expr: &Option<Expr>,
+        generation_expr_mode: &Option<GeneratedExpressionMode>,
+    ) -> GeneratedColumn {
+        match generation_expr {
+            Some(expr) => GeneratedColumn::Computed {
+                expression: expr.to_string(),
+                stored: matches!(generation_expr_mode, Some(GeneratedExpressionMode::Stored))
+                    || matches!(generated_as, GeneratedAs::ExpStored),
+            },
+            None => GeneratedColumn::Identity {
+                always: matches!(generated_as, GeneratedAs::Always),
+                sequence_options: sequence_options.as_ref()
+                    .filter(|options| !options.is_empty())
+                    .map(|options| options.iter().map(|o| o.to_string()).collect::<String>().trim().to_string()),
+            },
+        }
+    }
+
+    fn parse_table_options(&self, create_table: &CreateTable) -> TableOptions {
+        TableOptions {
+            engine: create_table.engine.as_ref().map(|engine| engine.name.clone()),
+            charset: create_table.default_charset.clone(),
+            collation: create_table.collation.clone(),
+            without_rowid: create_table.without_rowid,
         }
-        
-        let entity = Entity {
-            name: table_name.to_string(),
-            columns: parsed_columns,
-            position: Position::default(),
-            dimensions: Dimensions { width: 20, height: 10 },
-        };
-        
-        Ok((entity, relationships))
+    }
+
+    fn parse_identity_property(&self, kind: &IdentityPropertyKind) -> GeneratedColumn {
+        let (IdentityPropertyKind::Identity(property) | IdentityPropertyKind::Autoincrement(property)) = kind;
+        let sequence_options = property.parameters.as_ref().map(|parameters| match parameters {
+            IdentityPropertyFormatKind::FunctionCall(p) | IdentityPropertyFormatKind::StartAndIncrement(p) => {
+                format!("INCREMENT BY {} START WITH {}", p.increment, p.seed)
+            }
+        });
+        GeneratedColumn::Identity { always: false, sequence_options }
+    }
+
+    fn is_auto_increment(&self, tokens: &[Token]) -> bool {
+        tokens.iter().any(|token| {
+            matches!(token, Token::Word(word) if matches!(word.keyword, Keyword::AUTO_INCREMENT | Keyword::AUTOINCREMENT))
+        })
+    }
+
+    fn serial_base_type(&self, data_type: &DataType) -> Option<&'static str> {
+        match data_type {
+            DataType::Custom(name, modifiers) if modifiers.is_empty() => {
+                match object_name(name).to_uppercase().as_str() {
+                    "SMALLSERIAL" | "SERIAL2" => Some("SMALLINT"),
+                    "SERIAL" | "SERIAL4" => Some("INT"),
+                    "BIGSERIAL" | "SERIAL8" => Some("BIGINT"),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_index(&self, create_index: &CreateIndex) -> Index {
+        let table_name = object_name(&create_index.table_name);
+        let key_parts: Vec<String> = create_index.columns.iter().map(index_key_part).collect();
+        Index {
+            name: create_index.name.as_ref()
+                .map(object_name)
+                .unwrap_or_else(|| self.default_index_name(&table_name, &key_parts)),
+            key_parts,
+            unique: create_index.unique,
+            predicate: create_index.predicate.as_ref().map(|p| p.to_string()),
+        }
+    }
+
+    /// Name for an index declared without one, e.g. `idx_users_email`.
+    fn default_index_name(&self, table_name: &str, key_parts: &[String]) -> String {
+        let suffix: String = key_parts
+            .join("_")
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        format!("idx_{}_{}", table_name, suffix.trim_matches('_'))
+    }
+
+    /// Zip the local and referenced columns of a FOREIGN KEY constraint in
+    /// declaration order. Missing referenced columns are left empty so that
+    /// `resolve_implicit_references` can fill them from the target's key.
+    fn pair_foreign_key_columns(&self, fk_columns: &[Ident], referred_columns: &[Ident]) -> Vec<ColumnPair> {
+        fk_columns
+            .iter()
+            .enumerate()
+            .map(|(i, fk_col)| {
+                let ref_column = referred_columns.get(i)
+                    .map(ident_name)
+                    .unwrap_or_default();
+                ColumnPair::new(ident_name(fk_col), ref_column)
+            })
+            .collect()
+    }
+
+    /// `REFERENCES t` without a column list points at the primary key of `t`,
+    /// which may be declared after the referencing table.
+    fn resolve_implicit_references(&self, entities: &mut IndexMap<String, Entity>, relationships: &mut [Relationship]) {
+        for relationship in relationships.iter_mut() {
+            if relationship.column_pairs.iter().all(|pair| !pair.to_column.is_empty()) {
+                continue;
+            }
+
+            let target_keys: Vec<String> = entities.get(&relationship.to_table)
+                .map(|entity| entity.columns.iter()
+                    .filter(|c| c.is_primary_key)
+                    .map(|c| c.name.clone())
+                    .collect())
+                .unwrap_or_default();
+
+            for (i, pair) in relationship.column_pairs.iter_mut().enumerate() {
+                if pair.to_column.is_empty() {
+                    pair.to_column = target_keys.get(i).cloned().unwrap_or_else(|| "id".to_string());
+                }
+            }
+
+            if let Some(entity) = entities.get_mut(&relationship.from_table) {
+                for column in entity.columns.iter_mut() {
+                    if let Some(reference) = column.references.as_mut()
+                        && reference.table == relationship.to_table
+                        && relationship.local_columns().any(|name| name == column.name)
+                    {
+                        reference.column_pairs = relationship.column_pairs.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    /// A unique or primary-key FK is one-to-one, a nullable FK is optional,
+    /// and both FKs of a junction table (PK made of exactly two FKs) are
+    /// many-to-many.
+    fn infer_cardinality(&self, entities: &IndexMap<String, Entity>, relationships: &mut [Relationship]) {
+        let junctions: Vec<&str> = entities.values()
+            .filter(|entity| self.is_junction_table(entity, relationships))
+            .map(|entity| entity.name.as_str())
+            .collect();
+
+        for relationship in relationships.iter_mut() {
+            let Some(entity) = entities.get(&relationship.from_table) else {
+                continue;
+            };
+            let local_columns: Vec<&str> = relationship.local_columns().collect();
+
+            let optional = entity.columns.iter()
+                .any(|c| c.nullable && local_columns.contains(&c.name.as_str()));
+            let in_primary_key = local_columns.iter().all(|c| entity.primary_key_columns().any(|pk| pk == *c));
+            let relationship_type = if junctions.contains(&entity.name.as_str()) && in_primary_key {
+                RelationshipType::ManyToMany
+            } else if entity.is_unique_key(&local_columns) {
+                RelationshipType::OneToOne
+            } else {
+                RelationshipType::OneToMany
+            };
+
+            relationship.optional = optional;
+            relationship.relationship_type = relationship_type;
+        }
+    }
+
+    fn is_junction_table(&self, entity: &Entity, relationships: &[Relationship]) -> bool {
+        let primary_key: Vec<&str> = entity.primary_key_columns().collect();
+        let key_relationships: Vec<&Relationship> = relationships.iter()
+            .filter(|r| r.from_table == entity.name && r.local_columns().all(|c| primary_key.contains(&c)))
+            .collect();
+
+        key_relationships.len() == 2
+            && primary_key.iter().all(|pk| key_relationships.iter().any(|r| r.local_columns().any(|c| c == *pk)))
     }
 
     fn format_data_type(&self, data_type: &DataType) -> String {
@@ -167,7 +645,7 @@ impl SqlParser {
             DataType::Float(_) => "FLOAT".to_string(),
             DataType::Real => "REAL".to_string(),
             DataType::Double | DataType::DoublePrecision => "DOUBLE".to_string(),
-            DataType::Decimal(_) | DataType::Dec(_) | DataType::Numeric(_) => "DECIMAL".to_string(),
+            DataType::Decimal(info) | DataType::Dec(info) | DataType::Numeric(info) => format!("DECIMAL{}", info),
             DataType::Boolean => "BOOLEAN".to_string(),
             DataType::Date => "DATE".to_string(),
             DataType::Time(_, _) => "TIME".to_string(),
@@ -176,11 +654,69 @@ impl SqlParser {
             DataType::Uuid => "UUID".to_string(),
             DataType::JSON => "JSON".to_string(),
             DataType::Blob(_) => "BLOB".to_string(),
-            _ => format!("{:?}", data_type),
+            _ => data_type.to_string(),
         }
     }
 }
 
+/// Byte offset of a one-based line/column position in `sql`.
+fn byte_offset(sql: &str, location: Location) -> usize {
+    let mut line = 1;
+    let mut column = 1;
+    for (offset, c) in sql.char_indices() {
+        if line > location.line || (line == location.line && column >= location.column) {
+            return offset;
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    sql.len()
+}
+
+/// The location of a byte offset, the inverse of [`byte_offset`].
+fn location_at(sql: &str, offset: usize) -> Location {
+    let before = &sql[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |i| i + 1)..].chars().count() + 1;
+    Location { line: line as u64, column: column as u64 }
+}
+
+/// A location in text that starts at `base`, as a location in the whole.
+fn shift_location(location: Location, base: Location) -> Location {
+    if location.line == 1 {
+        Location { line: base.line, column: base.column + location.column - 1 }
+    } else {
+        Location { line: base.line + location.line - 1, column: location.column }
+    }
+}
+
+/// Identifier text without dialect quoting: `"users"`, `` `users` `` and
+/// `[users]` all become `users`.
+fn ident_name(ident: &Ident) -> String {
+    ident.value.clone()
+}
+
+fn object_name(name: &ObjectName) -> String {
+    name.0.iter().map(|ident| ident.value.as_str()).collect::<Vec<_>>().join(".")
+}
+
+fn index_key_part(key_part: &OrderByExpr) -> String {
+    let mut text = match &key_part.expr {
+        Expr::Identifier(ident) => ident_name(ident),
+        expr => expr.to_string(),
+    };
+    match key_part.asc {
+        Some(true) => text.push_str(" ASC"),
+        Some(false) => text.push_str(" DESC"),
+        None => {}
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,7 +729,7 @@ mod tests {
             email VARCHAR(255)
         );";
         
-        let parser = SqlParser::new();
+        let parser = SqlParser::new(SqlDialect::Generic);
         let schema = parser.parse_sql(sql).unwrap();
         
         assert_eq!(schema.entities.len(), 1);
@@ -213,6 +749,24 @@ mod tests {
         assert_eq!(name_col.data_type, "VARCHAR(100)");
     }
 
+    #[test]
+    fn test_dimensions_fit_columns() {
+        let columns: Vec<String> = (0..15).map(|i| format!("column_{} INT", i)).collect();
+        let sql = format!(
+            "CREATE TABLE wide (id INT PRIMARY KEY, a_rather_long_column_name VARCHAR(255), {});
+             ALTER TABLE wide ADD COLUMN added TEXT;",
+            columns.join(", ")
+        );
+
+        let schema = SqlParser::new(SqlDialect::Generic).parse_sql(&sql).unwrap();
+        let wide = &schema.entities["wide"];
+
+        // Border, then one line per column including the added one
+        assert_eq!(wide.dimensions.height, 18 + 2);
+        // Key icon, name, type, nullable and constraint marks, border
+        assert_eq!(wide.dimensions.width as usize, 3 + "a_rather_long_column_name".len() + 1 + "VARCHAR(255)".len() + 1 + 2 + 2);
+    }
+
     #[test]
     fn test_parse_tables_with_foreign_keys() {
         let sql = "
@@ -229,7 +783,7 @@ mod tests {
             );
         ";
         
-        let parser = SqlParser::new();
+        let parser = SqlParser::new(SqlDialect::Generic);
         let schema = parser.parse_sql(sql).unwrap();
         
         assert_eq!(schema.entities.len(), 2);
@@ -240,9 +794,8 @@ mod tests {
         assert_eq!(schema.relationships.len(), 1);
         let rel = &schema.relationships[0];
         assert_eq!(rel.from_table, "employees");
-        assert_eq!(rel.from_column, "dept_id");
         assert_eq!(rel.to_table, "departments");
-        assert_eq!(rel.to_column, "id");
+        assert_eq!(rel.column_pairs, vec![ColumnPair::new("dept_id", "id")]);
         
         // Check foreign key column is marked correctly
         let employees = &schema.entities["employees"];
@@ -252,6 +805,290 @@ mod tests {
         
         let fk_ref = dept_id_col.references.as_ref().unwrap();
         assert_eq!(fk_ref.table, "departments");
-        assert_eq!(fk_ref.column, "id");
+        assert_eq!(fk_ref.column_pairs, vec![ColumnPair::new("dept_id", "id")]);
+    }
+
+    #[test]
+    fn test_parse_composite_foreign_key() {
+        let sql = "
+            CREATE TABLE tenants_projects (
+                tenant_id INT NOT NULL,
+                id INT NOT NULL,
+                PRIMARY KEY (tenant_id, id)
+            );
+
+            CREATE TABLE tasks (
+                id INT PRIMARY KEY,
+                tenant_id INT NOT NULL,
+                project_id INT NOT NULL,
+                FOREIGN KEY (tenant_id, project_id) REFERENCES tenants_projects(tenant_id, id)
+            );
+        ";
+
+        let parser = SqlParser::new(SqlDialect::Generic);
+        let schema = parser.parse_sql(sql).unwrap();
+
+        // One relationship for the whole key, columns paired in order
+        assert_eq!(schema.relationships.len(), 1);
+        let rel = &schema.relationships[0];
+        assert_eq!(rel.column_pairs, vec![
+            ColumnPair::new("tenant_id", "tenant_id"),
+            ColumnPair::new("project_id", "id"),
+        ]);
+
+        let tasks = &schema.entities["tasks"];
+        let project_id = tasks.columns.iter().find(|c| c.name == "project_id").unwrap();
+        let fk_ref = project_id.references.as_ref().unwrap();
+        assert_eq!(fk_ref.table, "tenants_projects");
+        assert_eq!(fk_ref.column_pairs, rel.column_pairs);
+    }
+
+    #[test]
+    fn test_implicit_reference_uses_target_primary_key() {
+        let sql = "
+            CREATE TABLE tasks (
+                id INT PRIMARY KEY,
+                owner INT REFERENCES members
+            );
+
+            CREATE TABLE members (
+                member_no INT PRIMARY KEY
+            );
+        ";
+
+        let parser = SqlParser::new(SqlDialect::Generic);
+        let schema = parser.parse_sql(sql).unwrap();
+
+        let rel = &schema.relationships[0];
+        assert_eq!(rel.column_pairs, vec![ColumnPair::new("owner", "member_no")]);
+
+        let owner = schema.entities["tasks"].columns.iter().find(|c| c.name == "owner").unwrap();
+        assert_eq!(owner.references.as_ref().unwrap().column_pairs, rel.column_pairs);
+    }
+
+    #[test]
+    fn test_parse_unique_check_and_indexes() {
+        let sql = "
+            CREATE INDEX idx_accounts_created ON accounts (created_at DESC);
+
+            CREATE TABLE accounts (
+                id INT PRIMARY KEY,
+                email VARCHAR(255) UNIQUE,
+                tenant_id INT NOT NULL,
+                balance DECIMAL(10,2),
+                created_at TIMESTAMP,
+                CONSTRAINT uq_accounts_tenant_email UNIQUE (tenant_id, email),
+                CONSTRAINT ck_balance CHECK (balance >= 0)
+            );
+
+            CREATE UNIQUE INDEX idx_accounts_live ON accounts (tenant_id) WHERE balance > 0;
+            CREATE INDEX ON accounts (lower(email));
+        ";
+
+        let parser = SqlParser::new(SqlDialect::Generic);
+        let schema = parser.parse_sql(sql).unwrap();
+        let accounts = &schema.entities["accounts"];
+
+        assert_eq!(accounts.unique_constraints, vec![
+            UniqueConstraint { name: None, columns: vec!["email".to_string()] },
+            UniqueConstraint {
+                name: Some("uq_accounts_tenant_email".to_string()),
+                columns: vec!["tenant_id".to_string(), "email".to_string()],
+            },
+        ]);
+        assert_eq!(accounts.check_constraints, vec![CheckConstraint {
+            name: Some("ck_balance".to_string()),
+            expression: "balance >= 0".to_string(),
+        }]);
+
+        assert_eq!(accounts.indexes.len(), 3);
+        assert_eq!(accounts.indexes[0].key_parts, vec!["created_at DESC".to_string()]);
+        let partial = &accounts.indexes[1];
+        assert!(partial.unique);
+        assert_eq!(partial.predicate.as_deref(), Some("balance > 0"));
+        assert_eq!(accounts.indexes[2].name, "idx_accounts_lower_email");
+
+        assert!(accounts.is_unique_column("email"));
+        assert!(!accounts.is_unique_column("tenant_id"));
+        assert!(accounts.is_indexed_column("created_at"));
+        assert!(accounts.is_indexed_column("tenant_id"));
+        assert!(!accounts.is_indexed_column("balance"));
+    }
+
+    #[test]
+    fn test_parse_defaults_generated_and_comments() {
+        let sql = "
+            CREATE TABLE orders (
+                id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+                status VARCHAR(50) DEFAULT 'pending' COMMENT 'lifecycle state',
+                price INT,
+                tax INT AS (price / 10),
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            );
+        ";
+
+        let parser = SqlParser::new(SqlDialect::Generic);
+        let schema = parser.parse_sql(sql).unwrap();
+        let orders = &schema.entities["orders"];
+        let column = |name: &str| orders.columns.iter().find(|c| c.name == name).unwrap();
+
+        assert_eq!(column("id").generated, Some(GeneratedColumn::Identity { always: true, sequence_options: None }));
+        assert_eq!(column("status").default.as_deref(), Some("'pending'"));
+        assert_eq!(column("status").comment.as_deref(), Some("lifecycle state"));
+        assert_eq!(column("created_at").default.as_deref(), Some("CURRENT_TIMESTAMP"));
+        assert_eq!(column("tax").generated, Some(GeneratedColumn::Computed {
+            expression: "price / 10".to_string(),
+            stored: false,
+        }));
+        assert_eq!(column("price").default, None);
+    }
+
+    #[test]
+    fn test_parse_mysql_dump() {
+        let sql = "
+            CREATE TABLE `users` (
+                `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+                `email` VARCHAR(255) NOT NULL,
+                `updated_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+                PRIMARY KEY (`id`),
+                KEY `idx_email` (`email`)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+            CREATE TABLE `posts` (
+                `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+                `user_id` INT UNSIGNED NOT NULL,
+                CONSTRAINT `fk_posts_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)
+            ) ENGINE=InnoDB;
+        ";
+
+        let parser = SqlParser::new(SqlDialect::MySql);
+        let schema = parser.parse_sql(sql).unwrap();
+
+        let users = &schema.entities["users"];
+        let id = &users.columns[0];
+        assert_eq!(id.name, "id");
+        assert_eq!(id.data_type, "INT UNSIGNED");
+        assert!(id.is_primary_key);
+        assert_eq!(id.generated, Some(GeneratedColumn::Identity { always: false, sequence_options: None }));
+        assert_eq!(users.columns[2].on_update.as_deref(), Some("CURRENT_TIMESTAMP"));
+        assert_eq!(users.options.engine.as_deref(), Some("InnoDB"));
+        assert_eq!(users.options.charset.as_deref(), Some("utf8mb4"));
+        assert!(users.is_indexed_column("email"));
+
+        assert_eq!(schema.relationships[0].column_pairs, vec![ColumnPair::new("user_id", "id")]);
+        assert_eq!(schema.relationships[0].to_table, "users");
+    }
+
+    #[test]
+    fn test_parse_postgres_dump() {
+        let sql = "
+            CREATE TABLE accounts (
+                id BIGSERIAL PRIMARY KEY,
+                plan TEXT DEFAULT 'free'::text
+            );
+
+            COMMENT ON COLUMN accounts.plan IS 'billing plan';
+        ";
+
+        let parser = SqlParser::new(SqlDialect::PostgreSql);
+        let schema = parser.parse_sql(sql).unwrap();
+        let accounts = &schema.entities["accounts"];
+
+        assert_eq!(accounts.columns[0].data_type, "BIGINT");
+        assert!(!accounts.columns[0].nullable);
+        assert_eq!(accounts.columns[0].generated, Some(GeneratedColumn::Identity { always: false, sequence_options: None }));
+        assert_eq!(accounts.columns[1].default.as_deref(), Some("'free'::TEXT"));
+        assert_eq!(accounts.columns[1].comment.as_deref(), Some("billing plan"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_sqlite_and_mssql_options() {
+        let sqlite = "CREATE TABLE kv (k INTEGER PRIMARY KEY AUTOINCREMENT, v TEXT) WITHOUT ROWID;";
+        let schema = SqlParser::new(SqlDialect::Sqlite).parse_sql(sqlite).unwrap();
+        let kv = &schema.entities["kv"];
+        assert!(kv.options.without_rowid);
+        assert!(matches!(kv.columns[0].generated, Some(GeneratedColumn::Identity { .. })));
+
+        let mssql = "CREATE TABLE [dbo].[orders] ([id] INT IDENTITY(1, 1) PRIMARY KEY, [note] NVARCHAR(100));";
+        let schema = SqlParser::new(SqlDialect::MsSql).parse_sql(mssql).unwrap();
+        let orders = &schema.entities["dbo.orders"];
+        assert_eq!(orders.columns[0].name, "id");
+        assert_eq!(orders.columns[0].generated, Some(GeneratedColumn::Identity {
+            always: false,
+            sequence_options: Some("INCREMENT BY 1 START WITH 1".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_lenient_parse_reports_broken_statements() {
+        let sql = "CREATE TABLE users (id INT PRIMARY KEY);\n\nCREATE TABLE broken (\n    id INT PRIMARY KEY,\n    name VARCHAR(50) NOT NOT NULL\n);\n\nCREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));";
+        let parser = SqlParser::new(SqlDialect::Generic);
+
+        let (schema, errors) = parser.parse_sql_lenient(sql);
+        assert_eq!(schema.entities.len(), 2);
+        assert!(schema.entities.contains_key("posts"));
+        assert_eq!(schema.relationships.len(), 1);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].statement_index, 1);
+        assert_eq!(errors[0].line, 5);
+        assert_eq!(errors[0].column, 22);
+        assert!(!errors[0].message.contains("Line:"));
+
+        let strict = parser.parse_sql(sql).unwrap_err();
+        assert!(strict.to_string().starts_with("statement 2 (line 5, column 22)"));
+    }
+
+    #[test]
+    fn test_lenient_parse_survives_tokenizer_errors() {
+        let sql = "CREATE TABLE a (id INT);\nCREATE TABLE b (note TEXT DEFAULT 'oops);\nCREATE TABLE c (id INT);\n\
+                   CREATE TABLE d (\"oops INT);\nCREATE TABLE e (id INT);\nCREATE TABLE f (id INT,,);";
+        let (schema, errors) = SqlParser::new(SqlDialect::Generic).parse_sql_lenient(sql);
+
+        let mut names: Vec<&str> = schema.entities.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["a", "c", "e"]);
+        let found: Vec<(usize, usize, usize)> = errors.iter().map(|e| (e.statement_index, e.line, e.column)).collect();
+        assert_eq!(found, [(1, 2, 35), (3, 4, 17), (5, 6, 24)]);
+    }
+
+    #[test]
+    fn test_infer_relationship_cardinality() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY, manager_id INT REFERENCES users(id));
+            CREATE TABLE profiles (user_id INT PRIMARY KEY REFERENCES users(id));
+            CREATE TABLE passports (id INT PRIMARY KEY, user_id INT NOT NULL UNIQUE REFERENCES users(id));
+            CREATE TABLE posts (id INT PRIMARY KEY, author_id INT NOT NULL REFERENCES users(id));
+            CREATE TABLE tags (id INT PRIMARY KEY);
+            CREATE TABLE post_tags (
+                post_id INT REFERENCES posts(id),
+                tag_id INT REFERENCES tags(id),
+                added_by INT REFERENCES users(id),
+                PRIMARY KEY (post_id, tag_id)
+            );
+        ";
+
+        let schema = SqlParser::new(SqlDialect::Generic).parse_sql(sql).unwrap();
+        let find = |from: &str, to: &str| {
+            schema.relationships.iter().find(|r| r.from_table == from && r.to_table == to).unwrap()
+        };
+
+        let manager = find("users", "users");
+        assert_eq!(manager.relationship_type, RelationshipType::OneToMany);
+        assert!(manager.optional);
+
+        assert_eq!(find("profiles", "users").relationship_type, RelationshipType::OneToOne);
+        assert_eq!(find("passports", "users").relationship_type, RelationshipType::OneToOne);
+
+        let author = find("posts", "users");
+        assert_eq!(author.relationship_type, RelationshipType::OneToMany);
+        assert!(!author.optional);
+        assert_eq!(author.cardinality_labels(), ("N", "1"));
+
+        let tagged = find("post_tags", "posts");
+        assert_eq!(tagged.relationship_type, RelationshipType::ManyToMany);
+        assert_eq!(tagged.cardinality_labels(), ("N", "1"));
+        assert_eq!(find("post_tags", "tags").relationship_type, RelationshipType::ManyToMany);
+        assert_eq!(find("post_tags", "users").relationship_type, RelationshipType::OneToMany);
+    }
+}
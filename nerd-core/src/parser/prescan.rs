@@ -0,0 +1,153 @@
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Token, TokenWithLocation};
+
+/// What the pre-scan makes of one statement's tokens. sqlparser 0.52 cannot
+/// read MySQL's `RENAME TABLE` or `ALTER TABLE ... DROP FOREIGN KEY`, so
+/// those are picked out here before the rest goes to the parser.
+#[derive(Debug)]
+pub(super) enum Prescan {
+    /// Nothing for the pre-scan; parse the tokens as usual
+    Parse(Vec<TokenWithLocation>),
+    /// `RENAME TABLE a TO b, c TO d`
+    RenameTables(Vec<(String, String)>),
+    /// An `ALTER TABLE` with `DROP FOREIGN KEY` clauses, split into steps
+    /// that replay its clauses in source order
+    DropForeignKeys(Vec<Step>),
+    /// One of the statements above, but malformed
+    Invalid(String),
+}
+
+#[derive(Debug)]
+pub(super) enum Step {
+    /// A run of other clauses, as an `ALTER TABLE` of its own
+    Parse(Vec<TokenWithLocation>),
+    DropForeignKeys { table: String, names: Vec<String> },
+}
+
+pub(super) fn prescan(tokens: Vec<TokenWithLocation>) -> Prescan {
+    let significant: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !matches!(tokens[i].token, Token::Whitespace(_)))
+        .collect();
+    let keyword_at = |n: usize, keyword: Keyword| {
+        significant.get(n).is_some_and(|&i| matches!(&tokens[i].token, Token::Word(w) if w.keyword == keyword))
+    };
+
+    if keyword_at(0, Keyword::RENAME) && keyword_at(1, Keyword::TABLE) {
+        return match rename_tables(&tokens, &significant[2..]) {
+            Some(renames) => Prescan::RenameTables(renames),
+            None => Prescan::Invalid("Expected: RENAME TABLE old_name TO new_name [, ...]".to_string()),
+        };
+    }
+    if keyword_at(0, Keyword::ALTER) && keyword_at(1, Keyword::TABLE) {
+        return drop_foreign_keys(tokens, &significant);
+    }
+    Prescan::Parse(tokens)
+}
+
+/// `a TO b [, c TO d ...]`, from the significant tokens after `RENAME TABLE`.
+fn rename_tables(tokens: &[TokenWithLocation], significant: &[usize]) -> Option<Vec<(String, String)>> {
+    let mut renames = Vec::new();
+    let mut pos = 0;
+    loop {
+        let old_name = object_name(tokens, significant, &mut pos)?;
+        match significant.get(pos).map(|&i| &tokens[i].token) {
+            Some(Token::Word(w)) if w.keyword == Keyword::TO => pos += 1,
+            _ => return None,
+        }
+        let new_name = object_name(tokens, significant, &mut pos)?;
+        renames.push((old_name, new_name));
+
+        match significant.get(pos).map(|&i| &tokens[i].token) {
+            None => return Some(renames),
+            Some(Token::Comma) => pos += 1,
+            Some(_) => return None,
+        }
+    }
+}
+
+fn drop_foreign_keys(tokens: Vec<TokenWithLocation>, significant: &[usize]) -> Prescan {
+    // ALTER TABLE [IF EXISTS] name
+    let mut pos = 2;
+    let is_keyword = |pos: usize, keyword: Keyword| {
+        significant.get(pos).is_some_and(|&i| matches!(&tokens[i].token, Token::Word(w) if w.keyword == keyword))
+    };
+    if is_keyword(pos, Keyword::IF) && is_keyword(pos + 1, Keyword::EXISTS) {
+        pos += 2;
+    }
+    let Some(table) = object_name(&tokens, significant, &mut pos) else {
+        return Prescan::Parse(tokens);
+    };
+    let header_end = significant.get(pos).copied().unwrap_or(tokens.len());
+
+    // Clauses are separated by commas outside parentheses
+    let mut clauses: Vec<Vec<TokenWithLocation>> = vec![Vec::new()];
+    let mut depth = 0;
+    for token in &tokens[header_end..] {
+        match token.token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            Token::Comma if depth == 0 => {
+                clauses.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(clause) = clauses.last_mut() {
+            clause.push(token.clone());
+        }
+    }
+
+    // Runs of other clauses become statements of their own between the drops
+    let mut steps = Vec::new();
+    let mut kept: Vec<Vec<TokenWithLocation>> = Vec::new();
+    let flush = |kept: &mut Vec<Vec<TokenWithLocation>>, steps: &mut Vec<Step>| {
+        if kept.is_empty() {
+            return;
+        }
+        let mut statement = tokens[..header_end].to_vec();
+        for (i, clause) in kept.drain(..).enumerate() {
+            if i > 0 && let Some(last) = statement.last() {
+                let location = last.location;
+                statement.push(TokenWithLocation { token: Token::Comma, location });
+            }
+            statement.extend(clause);
+        }
+        steps.push(Step::Parse(statement));
+    };
+    for clause in clauses {
+        let words: Vec<&Token> = clause.iter().map(|t| &t.token).filter(|t| !matches!(t, Token::Whitespace(_))).collect();
+        match words.as_slice() {
+            [Token::Word(drop), Token::Word(foreign), Token::Word(key), Token::Word(name)]
+                if drop.keyword == Keyword::DROP && foreign.keyword == Keyword::FOREIGN && key.keyword == Keyword::KEY =>
+            {
+                flush(&mut kept, &mut steps);
+                match steps.last_mut() {
+                    Some(Step::DropForeignKeys { names, .. }) => names.push(name.value.clone()),
+                    _ => steps.push(Step::DropForeignKeys { table: table.clone(), names: vec![name.value.clone()] }),
+                }
+            }
+            _ => kept.push(clause),
+        }
+    }
+    if steps.is_empty() {
+        return Prescan::Parse(tokens);
+    }
+    flush(&mut kept, &mut steps);
+    Prescan::DropForeignKeys(steps)
+}
+
+/// A possibly qualified name such as `shop.orders`, unquoted.
+fn object_name(tokens: &[TokenWithLocation], significant: &[usize], pos: &mut usize) -> Option<String> {
+    let mut parts = Vec::new();
+    loop {
+        match significant.get(*pos).map(|&i| &tokens[i].token) {
+            Some(Token::Word(w)) => parts.push(w.value.clone()),
+            _ => return None,
+        }
+        *pos += 1;
+        match significant.get(*pos).map(|&i| &tokens[i].token) {
+            Some(Token::Period) => *pos += 1,
+            _ => return Some(parts.join(".")),
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use super::{ident_name, object_name, SqlParser};
+use crate::models::{Entity, Relationship};
+use sqlparser::ast::{AlterColumnOperation, AlterTableOperation};
+use indexmap::IndexMap;
+
+impl SqlParser {
+    /// Apply one `ALTER TABLE` operation to the schema built so far.
+    /// `table_name` follows the table through a rename so later operations
+    /// in the same statement still find it. Operations the diagram cannot
+    /// follow are returned as an error message rather than skipped.
+    pub(super) fn apply_alter_operation(
+        &self,
+        table_name: &mut String,
+        operation: &AlterTableOperation,
+        entities: &mut IndexMap<String, Entity>,
+        relationships: &mut Vec<Relationship>,
+    ) -> Result<(), String> {
+        match operation {
+            AlterTableOperation::AddColumn { column_def, .. } => {
+                if let Some(entity) = entities.get_mut(table_name.as_str()) {
+                    let column = self.parse_column(entity, column_def, relationships);
+                    entity.columns.push(column);
+                }
+            }
+            AlterTableOperation::DropColumn { column_name, .. } => {
+                drop_column(table_name, &ident_name(column_name), entities, relationships);
+            }
+            AlterTableOperation::RenameColumn { old_column_name, new_column_name } => {
+                rename_column(table_name, &ident_name(old_column_name), &ident_name(new_column_name), entities, relationships);
+            }
+            AlterTableOperation::AddConstraint(constraint) => {
+                if let Some(entity) = entities.get_mut(table_name.as_str()) {
+                    self.apply_table_constraint(entity, constraint, relationships);
+                }
+            }
+            AlterTableOperation::DropConstraint { name, .. } => {
+                drop_constraint(table_name, &ident_name(name), entities, relationships);
+            }
+            AlterTableOperation::RenameTable { table_name: new_name } => {
+                let new_name = object_name(new_name);
+                rename_table(table_name, &new_name, entities, relationships)?;
+                *table_name = new_name;
+            }
+            AlterTableOperation::RenameConstraint { old_name, new_name } => {
+                rename_constraint(table_name, &ident_name(old_name), &ident_name(new_name), entities, relationships);
+            }
+            AlterTableOperation::DropPrimaryKey => {
+                if let Some(entity) = entities.get_mut(table_name.as_str()) {
+                    entity.columns.iter_mut().for_each(|c| c.is_primary_key = false);
+                }
+            }
+            AlterTableOperation::AlterColumn { column_name, op } => {
+                let column = entities
+                    .get_mut(table_name.as_str())
+                    .and_then(|entity| entity.columns.iter_mut().find(|c| c.name == column_name.value));
+                if let Some(column) = column {
+                    match op {
+                        AlterColumnOperation::SetNotNull => column.nullable = false,
+                        AlterColumnOperation::DropNotNull => column.nullable = true,
+                        AlterColumnOperation::SetDefault { value } => column.default = Some(value.to_string()),
+                        AlterColumnOperation::DropDefault => column.default = None,
+                        AlterColumnOperation::SetDataType { data_type, .. } => {
+                            column.data_type = self.format_data_type(data_type);
+                        }
+                        AlterColumnOperation::AddGenerated { .. } => {
+                            return Err(format!("ALTER TABLE {} {} is not supported", table_name, operation));
+                        }
+                    }
+                }
+            }
+            // Nothing the diagram shows
+            AlterTableOperation::OwnerTo { .. }
+            | AlterTableOperation::EnableRowLevelSecurity
+            | AlterTableOperation::DisableRowLevelSecurity
+            | AlterTableOperation::EnableTrigger { .. }
+            | AlterTableOperation::DisableTrigger { .. }
+            | AlterTableOperation::EnableAlwaysTrigger { .. }
+            | AlterTableOperation::EnableReplicaTrigger { .. }
+            | AlterTableOperation::EnableRule { .. }
+            | AlterTableOperation::DisableRule { .. }
+            | AlterTableOperation::EnableAlwaysRule { .. }
+            | AlterTableOperation::EnableReplicaRule { .. }
+            | AlterTableOperation::SetTblProperties { .. } => {}
+            _ => return Err(format!("ALTER TABLE {} {} is not supported", table_name, operation)),
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn drop_table(table_name: &str, entities: &mut IndexMap<String, Entity>, relationships: &mut Vec<Relationship>) {
+    remove_relationships(entities, relationships, |r| r.from_table == table_name || r.to_table == table_name);
+    entities.shift_remove(table_name);
+}
+
+/// Rename a table and every reference to it. Renaming onto a table that
+/// already exists is refused, as the database would.
+pub(super) fn rename_table(
+    old_name: &str,
+    new_name: &str,
+    entities: &mut IndexMap<String, Entity>,
+    relationships: &mut [Relationship],
+) -> Result<(), String> {
+    if old_name != new_name && entities.contains_key(new_name) {
+        return Err(format!("Cannot rename table {} to {}: {} already exists", old_name, new_name, new_name));
+    }
+    // Keep the table's place in declaration order
+    let Some((index, _, mut entity)) = entities.shift_remove_full(old_name) else {
+        return Ok(());
+    };
+    entity.name = new_name.to_string();
+    entities.shift_insert(index, new_name.to_string(), entity);
+
+    for relationship in relationships.iter_mut() {
+        if relationship.from_table == old_name {
+            relationship.from_table = new_name.to_string();
+        }
+        if relationship.to_table == old_name {
+            relationship.to_table = new_name.to_string();
+        }
+    }
+
+    for column in entities.values_mut().flat_map(|entity| entity.columns.iter_mut()) {
+        if let Some(reference) = column.references.as_mut()
+            && reference.table == old_name
+        {
+            reference.table = new_name.to_string();
+        }
+    }
+    Ok(())
+}
+
+fn drop_column(table_name: &str, column_name: &str, entities: &mut IndexMap<String, Entity>, relationships: &mut Vec<Relationship>) {
+    remove_relationships(entities, relationships, |r| {
+        (r.from_table == table_name && r.local_columns().any(|name| name == column_name))
+            || (r.to_table == table_name && r.referenced_columns().any(|name| name == column_name))
+    });
+
+    // Constraints and indexes on the column go with it
+    if let Some(entity) = entities.get_mut(table_name) {
+        entity.columns.retain(|c| c.name != column_name);
+        entity.unique_constraints.retain(|u| !u.columns.iter().any(|c| c == column_name));
+        entity.indexes.retain(|i| !i.key_parts.iter().any(|part| key_part_column(part) == column_name));
+    }
+}
+
+fn rename_column(
+    table_name: &str,
+    old_name: &str,
+    new_name: &str,
+    entities: &mut IndexMap<String, Entity>,
+    relationships: &mut [Relationship],
+) {
+    let rename = |name: &mut String| {
+        if name == old_name {
+            *name = new_name.to_string();
+        }
+    };
+
+    if let Some(entity) = entities.get_mut(table_name) {
+        entity.columns.iter_mut().for_each(|c| rename(&mut c.name));
+        for unique in entity.unique_constraints.iter_mut() {
+            unique.columns.iter_mut().for_each(rename);
+        }
+        for part in entity.indexes.iter_mut().flat_map(|i| i.key_parts.iter_mut()) {
+            if key_part_column(part) == old_name {
+                *part = format!("{}{}", new_name, &part[old_name.len()..]);
+            }
+        }
+    }
+
+    for relationship in relationships.iter_mut() {
+        for pair in relationship.column_pairs.iter_mut() {
+            if relationship.from_table == table_name {
+                rename(&mut pair.from_column);
+            }
+            if relationship.to_table == table_name {
+                rename(&mut pair.to_column);
+            }
+        }
+    }
+
+    for entity in entities.values_mut() {
+        let is_renamed_table = entity.name == table_name;
+        for reference in entity.columns.iter_mut().filter_map(|c| c.references.as_mut()) {
+            for pair in reference.column_pairs.iter_mut() {
+                if is_renamed_table {
+                    rename(&mut pair.from_column);
+                }
+                if reference.table == table_name {
+                    rename(&mut pair.to_column);
+                }
+            }
+        }
+    }
+}
+
+fn drop_constraint(table_name: &str, name: &str, entities: &mut IndexMap<String, Entity>, relationships: &mut Vec<Relationship>) {
+    remove_relationships(entities, relationships, |r| r.from_table == table_name && r.constraint_name() == name);
+
+    if let Some(entity) = entities.get_mut(table_name) {
+        entity.unique_constraints.retain(|u| u.name.as_deref() != Some(name));
+        entity.check_constraints.retain(|c| c.name.as_deref() != Some(name));
+    }
+}
+
+/// MySQL `ALTER TABLE ... DROP FOREIGN KEY name`. Unlike `DROP CONSTRAINT`,
+/// the name can only be a foreign key, so a missing one is an error.
+pub(super) fn drop_foreign_key(
+    table_name: &str,
+    name: &str,
+    entities: &mut IndexMap<String, Entity>,
+    relationships: &mut Vec<Relationship>,
+) -> Result<(), String> {
+    let matches = |r: &Relationship| r.from_table == table_name && r.constraint_name() == name;
+    if !relationships.iter().any(matches) {
+        return Err(format!("Table {} has no foreign key named {}", table_name, name));
+    }
+    remove_relationships(entities, relationships, matches);
+    Ok(())
+}
+
+fn rename_constraint(
+    table_name: &str,
+    old_name: &str,
+    new_name: &str,
+    entities: &mut IndexMap<String, Entity>,
+    relationships: &mut [Relationship],
+) {
+    for relationship in relationships.iter_mut() {
+        if relationship.from_table == table_name && relationship.constraint_name() == old_name {
+            relationship.name = Some(new_name.to_string());
+        }
+    }
+    if let Some(entity) = entities.get_mut(table_name) {
+        let names = entity.unique_constraints.iter_mut().map(|u| &mut u.name)
+            .chain(entity.check_constraints.iter_mut().map(|c| &mut c.name));
+        for name in names.filter(|name| name.as_deref() == Some(old_name)) {
+            *name = Some(new_name.to_string());
+        }
+    }
+}
+
+/// Remove matching relationships and the foreign key markers they put on
+/// their local columns.
+fn remove_relationships(
+    entities: &mut IndexMap<String, Entity>,
+    relationships: &mut Vec<Relationship>,
+    predicate: impl Fn(&Relationship) -> bool,
+) {
+    let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(relationships).into_iter().partition(|r| predicate(r));
+    *relationships = kept;
+
+    for relationship in removed {
+        let Some(entity) = entities.get_mut(&relationship.from_table) else {
+            continue;
+        };
+        for column in entity.columns.iter_mut() {
+            if relationship.local_columns().any(|name| name == column.name)
+                && column.references.as_ref().is_some_and(|r| r.table == relationship.to_table)
+            {
+                column.references = None;
+                column.is_foreign_key = false;
+            }
+        }
+    }
+}
+
+/// The column an index key part such as `created_at DESC` is on.
+fn key_part_column(key_part: &str) -> &str {
+    key_part.split_whitespace().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{SqlDialect, SqlParser};
+
+    #[test]
+    fn test_alter_table_add_constraint_foreign_key() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY);
+            CREATE TABLE orders (id INT PRIMARY KEY, user_id INT);
+            ALTER TABLE orders ADD CONSTRAINT fk_orders_user FOREIGN KEY (user_id) REFERENCES users (id);
+        ";
+
+        let schema = SqlParser::new(SqlDialect::Generic).parse_sql(sql).unwrap();
+        assert_eq!(schema.relationships.len(), 1);
+        assert_eq!(schema.relationships[0].name.as_deref(), Some("fk_orders_user"));
+        let user_id = &schema.entities["orders"].columns[1];
+        assert!(user_id.is_foreign_key);
+        assert_eq!(user_id.references.as_ref().unwrap().table, "users");
+    }
+
+    #[test]
+    fn test_migration_history_replays_to_final_schema() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR(100), legacy_flag BOOLEAN);
+            CREATE TABLE posts (id INT PRIMARY KEY, author INT REFERENCES users(id), body TEXT);
+            CREATE TABLE scratch (id INT);
+            CREATE INDEX idx_users_name ON users (name);
+
+            ALTER TABLE users ADD COLUMN email VARCHAR(255) NOT NULL UNIQUE;
+            ALTER TABLE users DROP COLUMN legacy_flag;
+            ALTER TABLE users RENAME COLUMN name TO full_name;
+            ALTER TABLE posts RENAME COLUMN author TO author_id;
+            ALTER TABLE users RENAME TO accounts;
+            ALTER TABLE posts ADD CONSTRAINT ck_posts_body CHECK (body <> '');
+            DROP TABLE scratch;
+        ";
+
+        let schema = SqlParser::new(SqlDialect::PostgreSql).parse_sql(sql).unwrap();
+        assert!(!schema.entities.contains_key("scratch"));
+        assert!(!schema.entities.contains_key("users"));
+
+        let accounts = &schema.entities["accounts"];
+        let names: Vec<&str> = accounts.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "full_name", "email"]);
+        assert!(accounts.is_unique_column("email"));
+        assert_eq!(accounts.indexes[0].key_parts, vec!["full_name"]);
+
+        let posts = &schema.entities["posts"];
+        assert_eq!(posts.check_constraints.len(), 1);
+        let reference = posts.columns[1].references.as_ref().unwrap();
+        assert_eq!(reference.table, "accounts");
+        assert_eq!(reference.column_pairs[0].from_column, "author_id");
+
+        assert_eq!(schema.relationships.len(), 1);
+        assert_eq!(schema.relationships[0].from_table, "posts");
+        assert_eq!(schema.relationships[0].to_table, "accounts");
+        assert_eq!(schema.relationships[0].local_columns().collect::<Vec<_>>(), vec!["author_id"]);
+    }
+
+    #[test]
+    fn test_drop_constraint_and_referenced_table() {
+        let sql = "
+            CREATE TABLE teams (id INT PRIMARY KEY);
+            CREATE TABLE users (id INT PRIMARY KEY, team_id INT REFERENCES teams(id), manager_id INT);
+            ALTER TABLE users ADD CONSTRAINT fk_manager FOREIGN KEY (manager_id) REFERENCES users (id);
+            ALTER TABLE users DROP CONSTRAINT fk_manager;
+            DROP TABLE teams;
+        ";
+
+        let schema = SqlParser::new(SqlDialect::Generic).parse_sql(sql).unwrap();
+        assert!(schema.relationships.is_empty());
+        let users = &schema.entities["users"];
+        assert!(users.columns.iter().all(|c| !c.is_foreign_key && c.references.is_none()));
+    }
+
+    #[test]
+    fn test_mysql_rename_table_and_drop_foreign_key() {
+        let sql = "
+            CREATE TABLE a (id INT PRIMARY KEY);
+            CREATE TABLE c (id INT PRIMARY KEY, a_id INT, note TEXT, CONSTRAINT fk_c FOREIGN KEY (a_id) REFERENCES a (id));
+            RENAME TABLE a TO b;
+            CREATE TABLE d (id INT PRIMARY KEY, b_id INT REFERENCES b(id));
+            ALTER TABLE c DROP FOREIGN KEY fk_c, DROP COLUMN note;
+        ";
+
+        let (schema, errors) = SqlParser::new(SqlDialect::MySql).parse_sql_lenient(sql);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(!schema.entities.contains_key("a"));
+        assert_eq!(schema.relationships.len(), 1);
+        assert_eq!(schema.relationships[0].from_table, "d");
+        assert_eq!(schema.relationships[0].to_table, "b");
+
+        let c = &schema.entities["c"];
+        assert_eq!(c.columns.len(), 2);
+        assert!(c.columns.iter().all(|col| !col.is_foreign_key));
+    }
+
+    #[test]
+    fn test_drop_foreign_key_clauses_replay_in_source_order() {
+        let sql = "
+            CREATE TABLE a (id INT PRIMARY KEY);
+            CREATE TABLE b (id INT PRIMARY KEY, a_id INT, note TEXT);
+            ALTER TABLE b ADD CONSTRAINT fk_b FOREIGN KEY (a_id) REFERENCES a (id), DROP FOREIGN KEY fk_b, DROP COLUMN note;
+        ";
+
+        let (schema, errors) = SqlParser::new(SqlDialect::MySql).parse_sql_lenient(sql);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(schema.relationships.is_empty());
+        assert_eq!(schema.entities["b"].columns.len(), 2);
+    }
+
+    #[test]
+    fn test_unsupported_alter_and_rename_onto_existing_table_are_reported() {
+        let sql = "
+            CREATE TABLE a (id INT PRIMARY KEY);
+            CREATE TABLE b (id INT PRIMARY KEY);
+            ALTER TABLE a RENAME TO b;
+            ALTER TABLE a MODIFY COLUMN id BIGINT;
+            ALTER TABLE a ALTER COLUMN id SET DEFAULT 0;
+            ALTER TABLE a DROP FOREIGN KEY fk_missing;
+        ";
+
+        let (schema, errors) = SqlParser::new(SqlDialect::MySql).parse_sql_lenient(sql);
+        let statements: Vec<usize> = errors.iter().map(|e| e.statement_index).collect();
+        assert_eq!(statements, vec![2, 3, 5]);
+        assert!(errors[0].message.contains("already exists"));
+        assert_eq!(errors[0].line, 4);
+        assert!(schema.entities.contains_key("a") && schema.entities.contains_key("b"));
+        assert_eq!(schema.entities["a"].columns[0].default.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_drop_primary_key_and_rename_constraint() {
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY);
+            CREATE TABLE orders (id INT, user_id INT, CONSTRAINT fk_old FOREIGN KEY (user_id) REFERENCES users (id));
+            ALTER TABLE orders RENAME CONSTRAINT fk_old TO fk_new;
+        ";
+        let (schema, errors) = SqlParser::new(SqlDialect::PostgreSql).parse_sql_lenient(sql);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(schema.relationships[0].name.as_deref(), Some("fk_new"));
+
+        let sql = "
+            CREATE TABLE users (id INT PRIMARY KEY);
+            ALTER TABLE users DROP PRIMARY KEY;
+        ";
+        let (schema, errors) = SqlParser::new(SqlDialect::MySql).parse_sql_lenient(sql);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(!schema.entities["users"].columns[0].is_primary_key);
+    }
+}
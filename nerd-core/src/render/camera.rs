@@ -0,0 +1,250 @@
+use crate::layout::{UNITS_PER_COLUMN, UNITS_PER_ROW};
+use crate::models::{Column, Dimensions, Entity, Schema};
+use ratatui::layout::Rect;
+
+/// How much of each table is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    /// Every column
+    Columns,
+    /// Primary and foreign key columns
+    Keys,
+    /// Table names only
+    Names,
+}
+
+/// Zoom steps from closest to furthest, with the detail drawn at each.
+const ZOOM_LEVELS: [(f64, Detail); 5] = [
+    (1.0, Detail::Columns),
+    (0.75, Detail::Keys),
+    (0.5, Detail::Keys),
+    (0.35, Detail::Names),
+    (0.25, Detail::Names),
+];
+
+/// Cells moved by one pan step.
+const PAN_COLUMNS: f64 = 4.0;
+const PAN_ROWS: f64 = 2.0;
+
+/// The part of the canvas shown in the diagram view: an offset in canvas
+/// units and a zoom level.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Camera {
+    /// Canvas point drawn at the top-left corner of the view
+    pub x: f64,
+    pub y: f64,
+    zoom: usize,
+    /// Size of the view on the last frame, for zooming around its centre
+    viewport: Rect,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cells per canvas cell at this zoom level.
+    pub fn scale(&self) -> f64 {
+        ZOOM_LEVELS[self.zoom].0
+    }
+
+    pub fn detail(&self) -> Detail {
+        ZOOM_LEVELS[self.zoom].1
+    }
+
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    /// Screen cell of a canvas point, relative to the view's top-left.
+    /// Negative or past the view when the point is off screen.
+    pub fn to_screen(&self, x: f64, y: f64) -> (i32, i32) {
+        (
+            ((x - self.x) / UNITS_PER_COLUMN * self.scale()).floor() as i32,
+            ((y - self.y) / UNITS_PER_ROW * self.scale()).floor() as i32,
+        )
+    }
+
+    /// Columns listed in an entity's box at this zoom level.
+    pub fn visible_columns<'a>(&self, entity: &'a Entity) -> Vec<&'a Column> {
+        match self.detail() {
+            Detail::Columns => entity.columns.iter().collect(),
+            Detail::Keys => entity.columns.iter().filter(|c| c.is_primary_key || c.is_foreign_key).collect(),
+            Detail::Names => Vec::new(),
+        }
+    }
+
+    /// The entity's box in cells `(x, y, width, height)`, relative to the
+    /// view's top-left. It covers the entity's scaled footprint on the
+    /// canvas, cut down to what this detail level lists, so boxes keep the
+    /// layout's gaps at every zoom level.
+    pub fn entity_box(&self, entity: &Entity) -> (i32, i32, u16, u16) {
+        let (left, top) = self.to_screen(entity.position.x, entity.position.y);
+        let (right, bottom) = self.to_screen(
+            entity.position.x + entity.dimensions.width as f64 * UNITS_PER_COLUMN,
+            entity.position.y + entity.dimensions.height as f64 * UNITS_PER_ROW,
+        );
+        let listed = match self.detail() {
+            Detail::Columns => entity.dimensions,
+            _ => Dimensions::for_columns(entity, &self.visible_columns(entity)),
+        };
+        // At least a cell, so far-out tables do not vanish
+        let width = (right - left).clamp(1, listed.width.max(1) as i32) as u16;
+        let height = (bottom - top).clamp(1, listed.height.max(1) as i32) as u16;
+        (left, top, width, height)
+    }
+
+    /// Canvas rectangle `(x, y, width, height)` shown in a view of `area`'s size.
+    pub fn visible_region(&self, area: Rect) -> (f64, f64, f64, f64) {
+        (
+            self.x,
+            self.y,
+            area.width as f64 * UNITS_PER_COLUMN / self.scale(),
+            area.height as f64 * UNITS_PER_ROW / self.scale(),
+        )
+    }
+
+    /// Canvas distance covered by a number of screen cells.
+    pub fn cells_to_units(&self, columns: f64, rows: f64) -> (f64, f64) {
+        (columns * UNITS_PER_COLUMN / self.scale(), rows * UNITS_PER_ROW / self.scale())
+    }
+
+    /// Move the view by whole pan steps, e.g. `(1, 0)` to look right.
+    pub fn pan(&mut self, steps_x: i32, steps_y: i32) {
+        let (dx, dy) = self.cells_to_units(steps_x as f64 * PAN_COLUMNS, steps_y as f64 * PAN_ROWS);
+        self.x += dx;
+        self.y += dy;
+    }
+
+    pub fn zoom_in(&mut self) {
+        if self.zoom > 0 {
+            self.set_zoom(self.zoom - 1);
+        }
+    }
+
+    pub fn zoom_out(&mut self) {
+        if self.zoom + 1 < ZOOM_LEVELS.len() {
+            self.set_zoom(self.zoom + 1);
+        }
+    }
+
+    /// Change the zoom level, keeping the canvas point at the centre of the
+    /// view where it is.
+    fn set_zoom(&mut self, zoom: usize) {
+        let (center_x, center_y) = self.center();
+        self.zoom = zoom;
+        self.look_at(center_x, center_y);
+    }
+
+    /// Canvas point at the centre of the view.
+    fn center(&self) -> (f64, f64) {
+        (
+            self.x + self.viewport.width as f64 / 2.0 * UNITS_PER_COLUMN / self.scale(),
+            self.y + self.viewport.height as f64 / 2.0 * UNITS_PER_ROW / self.scale(),
+        )
+    }
+
+    /// Put a canvas point at the centre of the view.
+    fn look_at(&mut self, x: f64, y: f64) {
+        self.x = x - self.viewport.width as f64 / 2.0 * UNITS_PER_COLUMN / self.scale();
+        self.y = y - self.viewport.height as f64 / 2.0 * UNITS_PER_ROW / self.scale();
+    }
+
+    /// Whether all of the entity's box is in the view.
+    pub fn shows(&self, entity: &Entity) -> bool {
+        let (left, top, width, height) = self.entity_box(entity);
+        left >= 0
+            && top >= 0
+            && left + width as i32 <= self.viewport.width as i32
+            && top + height as i32 <= self.viewport.height as i32
+    }
+
+    pub fn center_on(&mut self, entity: &Entity) {
+        self.look_at(
+            entity.position.x + entity.dimensions.width as f64 * UNITS_PER_COLUMN / 2.0,
+            entity.position.y + entity.dimensions.height as f64 * UNITS_PER_ROW / 2.0,
+        );
+    }
+
+    /// Pick the closest zoom level at which every table fits in the view,
+    /// or the furthest one if none does, and centre the diagram.
+    pub fn fit(&mut self, schema: &Schema) {
+        let Some((left, top, right, bottom)) = schema
+            .entities
+            .values()
+            .map(|e| {
+                let right = e.position.x + e.dimensions.width as f64 * UNITS_PER_COLUMN;
+                let bottom = e.position.y + e.dimensions.height as f64 * UNITS_PER_ROW;
+                (e.position.x, e.position.y, right, bottom)
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+        else {
+            return;
+        };
+
+        let columns = (right - left) / UNITS_PER_COLUMN;
+        let rows = (bottom - top) / UNITS_PER_ROW;
+        self.zoom = ZOOM_LEVELS
+            .iter()
+            .position(|(scale, _)| columns * scale <= self.viewport.width as f64 && rows * scale <= self.viewport.height as f64)
+            .unwrap_or(ZOOM_LEVELS.len() - 1);
+        self.look_at((left + right) / 2.0, (top + bottom) / 2.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Dimensions, Position};
+
+    fn entity(name: &str, x: f64, y: f64) -> Entity {
+        Entity {
+            name: name.to_string(),
+            position: Position { x, y },
+            dimensions: Dimensions { width: 20, height: 10 },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_zoom_keeps_center_and_degrades_detail() {
+        let mut camera = Camera::new();
+        camera.set_viewport(Rect::new(0, 0, 100, 40));
+        let center = camera.center();
+        assert_eq!(camera.detail(), Detail::Columns);
+
+        camera.zoom_out();
+        assert_eq!(camera.detail(), Detail::Keys);
+        let (x, y) = camera.center();
+        assert!((x - center.0).abs() < 1e-9 && (y - center.1).abs() < 1e-9);
+
+        for _ in 0..10 {
+            camera.zoom_out();
+        }
+        assert_eq!(camera.detail(), Detail::Names);
+        for _ in 0..10 {
+            camera.zoom_in();
+        }
+        assert_eq!(camera.scale(), 1.0);
+    }
+
+    #[test]
+    fn test_fit_and_center_on() {
+        let mut schema = Schema::new();
+        schema.entities.insert("a".to_string(), entity("a", 0.0, 0.0));
+        schema.entities.insert("b".to_string(), entity("b", 1200.0, 800.0));
+        let mut camera = Camera::new();
+        camera.set_viewport(Rect::new(0, 0, 100, 40));
+
+        // 170 x 60 cells at full size only fit at half size
+        camera.fit(&schema);
+        assert_eq!(camera.scale(), 0.5);
+        let (left, top) = camera.to_screen(0.0, 0.0);
+        let (right, bottom) = camera.to_screen(1360.0, 960.0);
+        assert!(left >= 0 && top >= 0 && right <= 100 && bottom <= 40);
+
+        camera.center_on(&schema.entities["b"]);
+        let (x, y) = camera.to_screen(1280.0, 880.0);
+        assert_eq!((x, y), (50, 20));
+    }
+}
@@ -0,0 +1,53 @@
+use ratatui::layout::{Position, Rect};
+
+/// Where the last frame drew each entity and relationship line, so mouse
+/// clicks hit exactly what is on screen.
+#[derive(Debug, Default)]
+pub struct HitMap {
+    entities: Vec<(String, Rect)>,
+    /// Relationship index in the schema, and the cells of its line
+    relationships: Vec<(usize, Vec<(u16, u16)>)>,
+}
+
+impl HitMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entities.clear();
+        self.relationships.clear();
+    }
+
+    pub(super) fn add_entity(&mut self, name: &str, area: Rect) {
+        self.entities.push((name.to_string(), area));
+    }
+
+    pub(super) fn add_relationship(&mut self, index: usize, cells: Vec<(u16, u16)>) {
+        self.relationships.push((index, cells));
+    }
+
+    /// The entity drawn at a cell; the last one drawn wins where boxes overlap.
+    pub fn entity_at(&self, x: u16, y: u16) -> Option<&str> {
+        self.entities
+            .iter()
+            .rev()
+            .find(|(_, area)| area.contains(Position { x, y }))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The relationship whose line passes through a cell.
+    pub fn relationship_at(&self, x: u16, y: u16) -> Option<usize> {
+        self.relationships
+            .iter()
+            .find(|(_, cells)| cells.contains(&(x, y)))
+            .map(|&(index, _)| index)
+    }
+
+    pub(super) fn relationship_cells(&self, index: usize) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.relationships
+            .iter()
+            .filter(move |(i, _)| *i == index)
+            .flat_map(|(_, cells)| cells.iter().copied())
+    }
+}
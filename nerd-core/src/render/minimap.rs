@@ -0,0 +1,93 @@
+use super::DiagramRenderer;
+use crate::layout::{UNITS_PER_COLUMN, UNITS_PER_ROW};
+use crate::models::Schema;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    widgets::{
+        canvas::{Canvas, Rectangle},
+        Block, Borders, Clear,
+    },
+    Frame,
+};
+
+/// Largest minimap, in cells including its border.
+const MINIMAP_WIDTH: u16 = 32;
+const MINIMAP_HEIGHT: u16 = 10;
+
+/// Where the minimap goes in the diagram `area`; `None` when it is too
+/// small for one.
+fn minimap_area(area: Rect) -> Option<Rect> {
+    let width = MINIMAP_WIDTH.min(area.width / 3);
+    let height = MINIMAP_HEIGHT.min(area.height / 2);
+    (width >= 4 && height >= 3).then(|| Rect::new(area.right() - width, area.bottom() - height, width, height))
+}
+
+/// The part of the diagram `area` above the minimap, for panels on the
+/// right that would otherwise cover it.
+pub fn above_minimap(area: Rect) -> Rect {
+    match minimap_area(area) {
+        Some(minimap) => Rect { height: minimap.y - area.y, ..area },
+        None => area,
+    }
+}
+
+impl DiagramRenderer {
+    /// Whole diagram in braille in the bottom-right corner: every table, the
+    /// selected one highlighted, and the part the camera is showing.
+    pub(super) fn render_minimap(&self, f: &mut Frame, schema: &Schema, area: Rect, selected_entity: &Option<String>) {
+        let Some(minimap_area) = minimap_area(area) else {
+            return;
+        };
+
+        let boxes: Vec<(bool, Rectangle)> = schema
+            .entities
+            .values()
+            .map(|entity| {
+                let selected = selected_entity.as_ref() == Some(&entity.name);
+                let color = if selected { Color::Yellow } else { Color::Gray };
+                // The canvas y axis points up, so the diagram is drawn upside down
+                let rectangle = Rectangle {
+                    x: entity.position.x,
+                    y: -(entity.position.y + entity.dimensions.height as f64 * UNITS_PER_ROW),
+                    width: entity.dimensions.width as f64 * UNITS_PER_COLUMN,
+                    height: entity.dimensions.height as f64 * UNITS_PER_ROW,
+                    color,
+                };
+                (selected, rectangle)
+            })
+            .collect();
+
+        let (view_x, view_y, view_width, view_height) = self.camera.visible_region(area);
+        let view = Rectangle { x: view_x, y: -(view_y + view_height), width: view_width, height: view_height, color: Color::Cyan };
+
+        // Everything, including the view when it has wandered off the diagram
+        let (left, bottom, right, top) = boxes
+            .iter()
+            .map(|(_, r)| r)
+            .chain([&view])
+            .fold((f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY), |b, r| {
+                (b.0.min(r.x), b.1.min(r.y), b.2.max(r.x + r.width), b.3.max(r.y + r.height))
+            });
+
+        let minimap = Canvas::default()
+            .block(Block::default().borders(Borders::ALL).title("Map").border_style(Style::default().fg(Color::DarkGray)))
+            .marker(Marker::Braille)
+            .x_bounds([left, right])
+            .y_bounds([bottom, top])
+            .paint(|ctx| {
+                ctx.draw(&view);
+                // The selected table last, so it stays visible in a crowd
+                for (_, rectangle) in boxes.iter().filter(|(selected, _)| !selected) {
+                    ctx.draw(rectangle);
+                }
+                for (_, rectangle) in boxes.iter().filter(|(selected, _)| *selected) {
+                    ctx.draw(rectangle);
+                }
+            });
+
+        f.render_widget(Clear, minimap_area);
+        f.render_widget(minimap, minimap_area);
+    }
+}
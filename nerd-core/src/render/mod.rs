@@ -1,33 +1,178 @@
-use crate::models::{Entity, Schema, Relationship};
+mod camera;
+mod hit_map;
+mod minimap;
+mod neighborhood;
+
+use crate::models::{Column, Entity, Schema, Relationship, RelationshipType};
+use crate::history::History;
+use crate::search::SearchMatch;
 use ratatui::{
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::Line,
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Widget},
     Frame,
 };
+use std::collections::HashMap;
+
+pub use camera::{Camera, Detail};
+pub use hit_map::HitMap;
+pub use minimap::above_minimap;
+pub use neighborhood::{Neighborhood, Role};
+pub use nerd_router::{Axis, RouteCache, RouteRequest};
+
+const LINK_UP: u8 = 1;
+const LINK_DOWN: u8 = 2;
+const LINK_LEFT: u8 = 4;
+const LINK_RIGHT: u8 = 8;
+
+/// How relationship ends are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RelationshipNotation {
+    /// Crow's foot glyphs: `<`/`>` many, `┼` one, `○` optional
+    #[default]
+    CrowsFoot,
+    /// `1`, `N`, `0..1` text, for fonts without the glyphs
+    Labels,
+}
+
+/// Which side of an entity a line end touches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// An entity's whole box in screen cells. Unlike a `Rect` it may start
+/// left of or above the view, once the camera has panned past its corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntityBox {
+    x: i32,
+    y: i32,
+    width: u16,
+    height: u16,
+}
+
+impl EntityBox {
+    fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
 
+    fn center(&self) -> (i32, i32) {
+        (self.x + self.width as i32 / 2, self.y + self.height as i32 / 2)
+    }
+
+    /// The part of the box inside `area`; empty when none of it is.
+    fn clip(&self, area: Rect) -> Rect {
+        let left = self.x.max(area.left() as i32);
+        let top = self.y.max(area.top() as i32);
+        let right = self.right().min(area.right() as i32);
+        let bottom = self.bottom().min(area.bottom() as i32);
+        if right <= left || bottom <= top {
+            return Rect::new(area.x, area.y, 0, 0);
+        }
+        Rect::new(left as u16, top as u16, (right - left) as u16, (bottom - top) as u16)
+    }
+}
+
+#[derive(Default)]
 pub struct DiagramRenderer {
-    canvas_width: u16,
-    canvas_height: u16,
+    notation: RelationshipNotation,
+    camera: Camera,
+    show_minimap: bool,
+    /// Relationship drawn in the highlight color, by index in the schema
+    highlighted_relationship: Option<usize>,
+    /// How many hops around the selected entity are highlighted; 0 is off
+    neighborhood_hops: usize,
+    /// Relationships leaving a focused view, counted per boundary table
+    stubs: HashMap<String, usize>,
+    /// Column of the selected entity drawn highlighted
+    selected_column: Option<String>,
 }
 
 impl DiagramRenderer {
-    pub fn new(width: u16, height: u16) -> Self {
+    pub fn new() -> Self {
         Self {
-            canvas_width: width,
-            canvas_height: height,
+            notation: RelationshipNotation::default(),
+            camera: Camera::default(),
+            show_minimap: false,
+            highlighted_relationship: None,
+            neighborhood_hops: 0,
+            stubs: HashMap::new(),
+            selected_column: None,
         }
     }
 
-    pub fn render(&self, f: &mut Frame, schema: &Schema, area: Rect, selected_entity: &Option<String>) {
+    pub fn with_notation(mut self, notation: RelationshipNotation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    pub fn with_minimap(mut self, show_minimap: bool) -> Self {
+        self.show_minimap = show_minimap;
+        self
+    }
+
+    pub fn with_highlighted_relationship(mut self, relationship: Option<usize>) -> Self {
+        self.highlighted_relationship = relationship;
+        self
+    }
+
+    pub fn with_neighborhood_hops(mut self, hops: usize) -> Self {
+        self.neighborhood_hops = hops;
+        self
+    }
+
+    pub fn with_stubs(mut self, stubs: HashMap<String, usize>) -> Self {
+        self.stubs = stubs;
+        self
+    }
+
+    pub fn with_selected_column(mut self, column: Option<String>) -> Self {
+        self.selected_column = column;
+        self
+    }
+
+    /// Draw the diagram. Relationship routes come from `routes`, which only
+    /// recomputes them when the layout changes. `hits` is refilled with what
+    /// was drawn where, for the mouse.
+    pub fn render(
+        &self,
+        f: &mut Frame,
+        schema: &Schema,
+        area: Rect,
+        selected_entity: &Option<String>,
+        routes: &mut RouteCache,
+        hits: &mut HitMap,
+    ) {
+        hits.clear();
         if schema.entities.is_empty() {
             self.render_empty_state(f, area);
             return;
         }
 
-        self.render_entities(f, schema, area, selected_entity);
-        self.render_relationships(f, schema, area);
+        // Everything outside the selected entity's neighborhood is dimmed
+        let neighborhood = selected_entity
+            .as_deref()
+            .and_then(|selected| Neighborhood::around(schema, selected, self.neighborhood_hops));
+
+        self.render_entities(f, schema, area, selected_entity, hits, neighborhood.as_ref());
+        self.render_relationships(f, schema, area, routes, hits, neighborhood.as_ref());
+        if self.show_minimap {
+            self.render_minimap(f, schema, area, selected_entity);
+        }
     }
 
     fn render_empty_state(&self, f: &mut Frame, area: Rect) {
@@ -43,45 +188,63 @@ impl DiagramRenderer {
         schema: &Schema,
         area: Rect,
         selected_entity: &Option<String>,
+        hits: &mut HitMap,
+        neighborhood: Option<&Neighborhood>,
     ) {
         for (entity_name, entity) in &schema.entities {
-            let entity_area = self.calculate_entity_area(entity, area);
+            let entity_box = self.entity_box(entity, area);
+            let entity_area = entity_box.clip(area);
+            if entity_area.is_empty() {
+                continue;
+            }
             let is_selected = selected_entity.as_ref() == Some(entity_name);
+            let role = match neighborhood {
+                Some(neighborhood) => neighborhood.entities.get(entity_name).copied(),
+                None => is_selected.then_some(Role::Selected),
+            };
+            let dimmed = neighborhood.is_some() && role.is_none();
+            hits.add_entity(entity_name, entity_area);
             
-            self.render_single_entity(f, entity, entity_area, is_selected);
+            self.render_single_entity(f, entity, entity_box, area, role, dimmed);
         }
     }
 
+    /// Draw the whole box into a scratch buffer, then copy over the cells
+    /// inside `area`, so a box partly out of view is cut rather than shrunk.
     fn render_single_entity(
         &self,
         f: &mut Frame,
         entity: &Entity,
+        entity_box: EntityBox,
         area: Rect,
-        is_selected: bool,
+        role: Option<Role>,
+        dimmed: bool,
     ) {
-        let border_style = if is_selected {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::White)
+        let border_style = match role {
+            Some(role) => Style::default().fg(role_color(role)).add_modifier(Modifier::BOLD),
+            None if dimmed => Style::default().fg(Color::DarkGray),
+            None => Style::default().fg(Color::White),
         };
 
-        let title_style = if is_selected {
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        let title_style = match role {
+            Some(role) => Style::default().fg(role_color(role)).add_modifier(Modifier::BOLD),
+            None if dimmed => Style::default().fg(Color::DarkGray),
+            None => Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
         };
 
         let block = Block::default()
-            .title(entity.name.clone())
+            .title(if entity.pinned { format!("📌 {}", entity.name) } else { entity.name.clone() })
             .title_style(title_style)
             .borders(Borders::ALL)
             .border_style(border_style);
 
-        let inner_area = block.inner(area);
+        let full_area = Rect::new(0, 0, entity_box.width, entity_box.height);
+        let inner_area = block.inner(full_area);
+        let selected_column = self.selected_column.as_deref().filter(|_| role == Some(Role::Selected));
 
-        let columns: Vec<ListItem> = entity
-            .columns
-            .iter()
+        let columns: Vec<ListItem> = self
+            .visible_columns(entity)
+            .into_iter()
             .map(|col| {
                 let key_indicator = if col.is_primary_key {
                     "🗝 "
@@ -93,21 +256,37 @@ impl DiagramRenderer {
 
                 let nullable_indicator = if col.nullable { "?" } else { "" };
 
+                let constraint_indicator = if entity.is_unique_column(&col.name) {
+                    " ◆"
+                } else if entity.is_indexed_column(&col.name) {
+                    " ◇"
+                } else {
+                    ""
+                };
+
                 let column_text = format!(
-                    "{}{} {}{}",
+                    "{}{} {}{}{}",
                     key_indicator,
                     col.name,
                     col.data_type,
-                    nullable_indicator
+                    nullable_indicator,
+                    constraint_indicator
                 );
 
-                let style = if col.is_primary_key {
+                let style = if dimmed {
+                    Style::default().fg(Color::DarkGray)
+                } else if col.is_primary_key {
                     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
                 } else if col.is_foreign_key {
                     Style::default().fg(Color::Magenta)
                 } else {
                     Style::default().fg(Color::White)
                 };
+                let style = if selected_column == Some(col.name.as_str()) {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style
+                };
 
                 ListItem::new(column_text).style(style)
             })
@@ -115,8 +294,28 @@ impl DiagramRenderer {
 
         let column_list = List::new(columns).block(Block::default());
 
-        f.render_widget(block, area);
-        f.render_widget(column_list, inner_area);
+        let mut scratch = Buffer::empty(full_area);
+        block.render(full_area, &mut scratch);
+        Widget::render(column_list, inner_area, &mut scratch);
+
+        // Boundary tables of a focused view carry the number of
+        // relationships leading out of it on the bottom border
+        if let Some(count) = self.stubs.get(&entity.name) {
+            let marker = format!("+{}↗", count);
+            let width = marker.chars().count() as u16;
+            if full_area.height >= 2 && full_area.width >= width + 2 {
+                let style = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
+                scratch.set_string(full_area.right() - width - 1, full_area.bottom() - 1, marker, style);
+            }
+        }
+
+        let visible = entity_box.clip(area);
+        for y in visible.top()..visible.bottom() {
+            for x in visible.left()..visible.right() {
+                let source = ((x as i32 - entity_box.x) as u16, (y as i32 - entity_box.y) as u16);
+                f.buffer_mut()[(x, y)] = scratch[source].clone();
+            }
+        }
     }
 
     fn render_relationships(
@@ -124,112 +323,268 @@ impl DiagramRenderer {
         f: &mut Frame,
         schema: &Schema,
         area: Rect,
+        routes: &mut RouteCache,
+        hits: &mut HitMap,
+        neighborhood: Option<&Neighborhood>,
     ) {
-        for relationship in &schema.relationships {
+        let entity_areas: Vec<Rect> = schema.entities
+            .values()
+            .map(|entity| self.calculate_entity_area(entity, area))
+            .collect();
+
+        let mut edges = Vec::new();
+        for (index, relationship) in schema.relationships.iter().enumerate() {
             if let (Some(from_entity), Some(to_entity)) = (
                 schema.entities.get(&relationship.from_table),
                 schema.entities.get(&relationship.to_table),
             ) {
-                self.render_relationship_line(f, from_entity, to_entity, relationship, schema, area);
+                let from_box = self.entity_box(from_entity, area);
+                let to_box = self.entity_box(to_entity, area);
+                let (from_area, to_area) = (from_box.clip(area), to_box.clip(area));
+                // Lines to tables outside the view have nowhere to end
+                if from_area.is_empty() || to_area.is_empty() {
+                    continue;
+                }
+                let (from_point, to_point) = self.calculate_column_connection_points(
+                    from_entity,
+                    to_entity,
+                    relationship,
+                    &from_box,
+                    &to_box
+                );
+                // Nor do lines leaving a column row that is scrolled away
+                let (Some(from_point), Some(to_point)) = (cell_in(area, from_point), cell_in(area, to_point)) else {
+                    continue;
+                };
+                edges.push((index, relationship, from_area, to_area, from_point, to_point));
+            }
+        }
+
+        let requests: Vec<RouteRequest> = edges
+            .iter()
+            .map(|&(_, _, from_area, to_area, from, to)| RouteRequest {
+                from,
+                from_axis: side_of(from, &from_area).axis(),
+                to,
+                to_axis: side_of(to, &to_area).axis(),
+            })
+            .collect();
+        let bounds = |rect: &Rect| (rect.x, rect.y, rect.width, rect.height);
+        let obstacles: Vec<_> = entity_areas.iter().map(bounds).collect();
+        let paths = routes.routes(bounds(&area), &obstacles, &requests);
+
+        // Merge every route into one grid so crossings and shared channels
+        // get junction glyphs instead of overwriting each other
+        let mut links: HashMap<(u16, u16), u8> = HashMap::new();
+        for (&(index, _, from_area, to_area, from, to), path) in edges.iter().zip(paths) {
+            hits.add_relationship(index, path.clone().unwrap_or_else(|| vec![from, to]));
+            match path {
+                Some(path) => {
+                    for pair in path.windows(2) {
+                        let (a, b) = (pair[0], pair[1]);
+                        let (towards_b, towards_a) = if a.0 < b.0 {
+                            (LINK_RIGHT, LINK_LEFT)
+                        } else if a.0 > b.0 {
+                            (LINK_LEFT, LINK_RIGHT)
+                        } else if a.1 < b.1 {
+                            (LINK_DOWN, LINK_UP)
+                        } else {
+                            (LINK_UP, LINK_DOWN)
+                        };
+                        *links.entry(a).or_default() |= towards_b;
+                        *links.entry(b).or_default() |= towards_a;
+                    }
+                    *links.entry(from).or_default() |= side_of(from, &from_area).link_towards_entity();
+                    *links.entry(to).or_default() |= side_of(to, &to_area).link_towards_entity();
+                }
+                // No way around the obstacles: fall back to the shape-based drawing
+                None => self.draw_connection_line_avoiding_entities(f, from, to, &entity_areas, area),
+            }
+        }
+        for (&(x, y), &link) in &links {
+            self.draw_line_segment(f, x, y, junction_glyph(link), area);
+        }
+        if let Some(neighborhood) = neighborhood {
+            for &(index, ..) in &edges {
+                if !neighborhood.relationships.contains_key(&index) {
+                    self.restyle_relationship(f, hits, index, Style::default().fg(Color::DarkGray), area);
+                }
+            }
+            for (&index, &role) in &neighborhood.relationships {
+                self.restyle_relationship(f, hits, index, Style::default().fg(role_color(role)), area);
+            }
+        }
+        if let Some(index) = self.highlighted_relationship {
+            let highlight = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+            self.restyle_relationship(f, hits, index, highlight, area);
+        }
+
+        for (_, relationship, from_area, to_area, from_point, to_point) in edges {
+            self.render_relationship_ends(f, relationship, (&from_area, from_point), (&to_area, to_point), area);
+        }
+    }
+
+    fn restyle_relationship(&self, f: &mut Frame, hits: &HitMap, index: usize, style: Style, area: Rect) {
+        for (x, y) in hits.relationship_cells(index) {
+            if area.contains((x, y).into()) {
+                f.buffer_mut()[(x, y)].set_style(style);
             }
         }
     }
 
-    fn render_relationship_line(
+    fn render_relationship_ends(
         &self,
         f: &mut Frame,
-        from_entity: &Entity,
-        to_entity: &Entity,
         relationship: &Relationship,
-        schema: &Schema,
+        (from_area, from_point): (&Rect, (u16, u16)),
+        (to_area, to_point): (&Rect, (u16, u16)),
         area: Rect,
     ) {
-        let from_area = self.calculate_entity_area(from_entity, area);
-        let to_area = self.calculate_entity_area(to_entity, area);
-
-        // Calculate connection points for specific columns
-        let (from_point, to_point) = self.calculate_column_connection_points(
-            from_entity, 
-            to_entity, 
-            &relationship.from_column,
-            &relationship.to_column,
-            &from_area, 
-            &to_area
-        );
+        match self.notation {
+            RelationshipNotation::CrowsFoot => {
+                // The child side may have no rows; the parent side is optional
+                // only when the foreign key is nullable
+                let many = relationship.relationship_type != RelationshipType::OneToOne;
+                self.draw_crows_foot(f, from_point, from_area, many, true, area);
+                self.draw_crows_foot(f, to_point, to_area, false, relationship.optional, area);
+            }
+            RelationshipNotation::Labels => {
+                let (child_label, parent_label) = relationship.cardinality_labels();
+                self.draw_cardinality_label(f, from_point, from_area, child_label, area);
+                self.draw_cardinality_label(f, to_point, to_area, parent_label, area);
+            }
+        }
+    }
 
-        // Collect all entity areas to avoid drawing through them
-        let entity_areas: Vec<Rect> = schema.entities
-            .values()
-            .map(|entity| self.calculate_entity_area(entity, area))
-            .collect();
-            
-        self.draw_connection_line_avoiding_entities(f, from_point, to_point, &entity_areas, area);
+    /// Draw a crow's foot end: the cell touching the entity shows one (`┼`)
+    /// or many (`<` `>` `∧` `∨`, opening towards the entity), the cell
+    /// before it mandatory (`┼`) or optional (`○`).
+    fn draw_crows_foot(&self, f: &mut Frame, point: (u16, u16), entity_area: &Rect, many: bool, optional: bool, area: Rect) {
+        let side = side_of(point, entity_area);
+        let inner = if many {
+            match side {
+                Side::Left => "<",
+                Side::Right => ">",
+                Side::Top => "∧",
+                Side::Bottom => "∨",
+            }
+        } else {
+            "┼"
+        };
+        let outer = if optional { "○" } else { "┼" };
+
+        let outer_point = match side {
+            Side::Left => (point.0.saturating_sub(1), point.1),
+            Side::Right => (point.0 + 1, point.1),
+            Side::Top => (point.0, point.1.saturating_sub(1)),
+            Side::Bottom => (point.0, point.1 + 1),
+        };
+
+        for ((x, y), glyph) in [(point, inner), (outer_point, outer)] {
+            let glyph_area = Rect { x, y, width: 1, height: 1 };
+            if area.contains(glyph_area.as_position()) {
+                let glyph_widget = Paragraph::new(glyph)
+                    .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+                f.render_widget(glyph_widget, glyph_area);
+            }
+        }
     }
 
+    /// Write a multiplicity next to where a line meets an entity: above the
+    /// line on the left/right edges, beside it on the top/bottom edges.
+    fn draw_cardinality_label(&self, f: &mut Frame, point: (u16, u16), entity_area: &Rect, label: &str, area: Rect) {
+        let width = label.chars().count() as u16;
+        let (x, y) = match side_of(point, entity_area) {
+            Side::Left => (point.0.saturating_sub(width - 1), point.1.saturating_sub(1)),
+            Side::Right => (point.0, point.1.saturating_sub(1)),
+            Side::Top | Side::Bottom => (point.0 + 1, point.1),
+        };
 
+        let label_area = Rect { x, y, width, height: 1 };
+        if area.contains(label_area.as_position()) && label_area.right() <= area.right() {
+            let label_widget = Paragraph::new(label)
+                .style(Style::default().fg(Color::Yellow));
+            f.render_widget(label_widget, label_area);
+        }
+    }
+
+
+    /// Where a relationship's line leaves and enters its entities: just
+    /// outside the facing borders, level with the key columns. Taken from
+    /// the whole boxes, so the points may lie outside the view.
     fn calculate_column_connection_points(
         &self,
         from_entity: &Entity,
         to_entity: &Entity,
-        from_column_name: &str,
-        to_column_name: &str,
-        from_area: &Rect,
-        to_area: &Rect,
-    ) -> ((u16, u16), (u16, u16)) {
-        // Calculate the Y position of the specific columns within their entities
-        let from_column_y = self.calculate_column_y_position(from_entity, from_column_name, from_area);
-        let to_column_y = self.calculate_column_y_position(to_entity, to_column_name, to_area);
+        relationship: &Relationship,
+        from_box: &EntityBox,
+        to_box: &EntityBox,
+    ) -> ((i32, i32), (i32, i32)) {
+        // Calculate the Y position of the key columns within their entities
+        let from_column_y = self.calculate_key_y_position(from_entity, relationship.local_columns(), from_box);
+        let to_column_y = self.calculate_key_y_position(to_entity, relationship.referenced_columns(), to_box);
 
         // Determine which edges to connect based on entity positions
-        let from_center_x = from_area.x + from_area.width / 2;
-        let from_center_y = from_area.y + from_area.height / 2;
-        let to_center_x = to_area.x + to_area.width / 2;
-        let to_center_y = to_area.y + to_area.height / 2;
+        let (from_center_x, from_center_y) = from_box.center();
+        let (to_center_x, to_center_y) = to_box.center();
 
         let from_point = if to_center_x > from_center_x {
             // Connect from right edge at column height (outside the border)
-            (from_area.x + from_area.width, from_column_y)
+            (from_box.right(), from_column_y)
         } else if to_center_x < from_center_x {
             // Connect from left edge at column height (outside the border)
-            (from_area.x.saturating_sub(1), from_column_y)
+            (from_box.x - 1, from_column_y)
         } else if to_center_y > from_center_y {
             // Connect from bottom edge (outside the border)
-            (from_center_x, from_area.y + from_area.height)
+            (from_center_x, from_box.bottom())
         } else {
             // Connect from top edge (outside the border)
-            (from_center_x, from_area.y.saturating_sub(1))
+            (from_center_x, from_box.y - 1)
         };
 
         let to_point = if from_center_x > to_center_x {
             // Connect to right edge at column height (outside the border)
-            (to_area.x + to_area.width, to_column_y)
+            (to_box.right(), to_column_y)
         } else if from_center_x < to_center_x {
             // Connect to left edge at column height (outside the border)
-            (to_area.x.saturating_sub(1), to_column_y)
+            (to_box.x - 1, to_column_y)
         } else if from_center_y > to_center_y {
             // Connect to bottom edge (outside the border)
-            (to_center_x, to_area.y + to_area.height)
+            (to_center_x, to_box.bottom())
         } else {
             // Connect to top edge (outside the border)
-            (to_center_x, to_area.y.saturating_sub(1))
+            (to_center_x, to_box.y - 1)
         };
 
         (from_point, to_point)
     }
 
-    fn calculate_column_y_position(&self, entity: &Entity, column_name: &str, entity_area: &Rect) -> u16 {
+    fn calculate_column_y_position(&self, entity: &Entity, column_name: &str, entity_box: &EntityBox) -> i32 {
         // The inner area starts 1 pixel down from the entity area (for the border)
-        let inner_y = entity_area.y + 1;
+        let inner_y = entity_box.y + 1;
         
-        // Find the column index
-        let column_index = entity.columns
+        // Find the column index among the columns drawn at this zoom level
+        let column_index = self.visible_columns(entity)
             .iter()
             .position(|col| col.name == column_name)
             .unwrap_or(0);
         
         // Each column takes up 1 line in the list
-        // Add the column index to the inner Y position
-        inner_y + column_index as u16
+        // Add the column index to the inner Y position, staying inside the box
+        (inner_y + column_index as i32).min((entity_box.bottom() - 2).max(inner_y))
+    }
+
+    /// A composite key is drawn as a single edge anchored at its topmost column.
+    fn calculate_key_y_position<'a>(
+        &self,
+        entity: &Entity,
+        column_names: impl Iterator<Item = &'a str>,
+        entity_box: &EntityBox,
+    ) -> i32 {
+        column_names
+            .map(|name| self.calculate_column_y_position(entity, name, entity_box))
+            .min()
+            .unwrap_or(entity_box.y + 1)
     }
 
     fn draw_connection_line_avoiding_entities(
@@ -257,9 +612,6 @@ impl DiagramRenderer {
         // 2. Diagonal segment (if needed)  
         // 3. Horizontal/vertical segment to end
         self.draw_smooth_line_avoiding_entities(f, from, to, entity_areas, area);
-        
-        // Draw arrow heads to show direction (from FK to PK)
-        self.draw_arrow_head(f, to, from, area);
     }
 
     fn draw_smooth_line_avoiding_entities(
@@ -493,54 +845,33 @@ impl DiagramRenderer {
 
     fn point_is_inside_entity(&self, x: u16, y: u16, entity_area: &Rect) -> bool {
         x >= entity_area.x && 
-        x <= entity_area.x + entity_area.width - 1 &&
+        x < entity_area.x + entity_area.width &&
         y >= entity_area.y && 
-        y <= entity_area.y + entity_area.height - 1
+        y < entity_area.y + entity_area.height
     }
     
-    fn draw_arrow_head(
-        &self,
-        f: &mut Frame,
-        tip: (u16, u16),
-        from: (u16, u16),
-        area: Rect,
-    ) {
-        let dx = from.0 as i32 - tip.0 as i32;
-        let dy = from.1 as i32 - tip.1 as i32;
-        
-        // Determine arrow character based on direction
-        let arrow_char = if dx.abs() > dy.abs() {
-            if dx > 0 { "◄" } else { "►" }
-        } else {
-            if dy > 0 { "▲" } else { "▼" }
-        };
-        
-        let arrow_area = Rect {
-            x: tip.0,
-            y: tip.1,
-            width: 1,
-            height: 1,
-        };
-        
-        let arrow_widget = Paragraph::new(arrow_char)
-            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
-        
-        if arrow_area.intersects(area) {
-            f.render_widget(arrow_widget, arrow_area);
+    /// The entity's whole box as seen through the camera, at its offset
+    /// from `canvas_area`. At full zoom canvas units map to cells at a fixed
+    /// scale, so boxes the layout kept apart stay apart on screen.
+    fn entity_box(&self, entity: &Entity, canvas_area: Rect) -> EntityBox {
+        let (x, y, width, height) = self.camera.entity_box(entity);
+        EntityBox {
+            x: canvas_area.x as i32 + x,
+            y: canvas_area.y as i32 + y,
+            width,
+            height,
         }
     }
 
+    /// The part of the entity's box inside `canvas_area`, empty when it is
+    /// out of view.
     fn calculate_entity_area(&self, entity: &Entity, canvas_area: Rect) -> Rect {
-        let x_ratio = entity.position.x / (self.canvas_width as f64);
-        let y_ratio = entity.position.y / (self.canvas_height as f64);
-
-        let x = (x_ratio * canvas_area.width as f64) as u16 + canvas_area.x;
-        let y = (y_ratio * canvas_area.height as f64) as u16 + canvas_area.y;
-
-        let width = entity.dimensions.width.min(canvas_area.width / 4);
-        let height = entity.dimensions.height.min(canvas_area.height / 4);
+        self.entity_box(entity, canvas_area).clip(canvas_area)
+    }
 
-        Rect { x, y, width, height }
+    /// Columns listed in the entity's box at the camera's zoom level.
+    fn visible_columns<'a>(&self, entity: &'a Entity) -> Vec<&'a Column> {
+        self.camera.visible_columns(entity)
     }
 }
 
@@ -556,14 +887,40 @@ pub fn render_help_screen(f: &mut Frame, area: Rect) {
         Line::from("  g          - Generate SQL from diagram"),
         Line::from("  r          - Refresh/re-layout diagram"),
         Line::from("  v          - Validate schema"),
+        Line::from("  t          - Toggle crow's foot / label notation"),
+        Line::from("  a          - Switch force-directed / hierarchical layout"),
+        Line::from("  [ / ]      - Step back/forward through loaded migrations"),
         Line::from("  ?          - Show this help screen"),
         Line::from("  Esc        - Return to diagram view"),
         Line::from(""),
+        Line::from("View:"),
+        Line::from("  hjkl/↑↓←→  - Pan"),
+        Line::from("  + / -      - Zoom in/out (columns, keys only, names only)"),
+        Line::from("  f          - Fit all entities in view"),
+        Line::from("  c          - Center on selected entity"),
+        Line::from("  m          - Toggle minimap"),
+        Line::from(""),
+        Line::from("Mouse:"),
+        Line::from("  Click      - Select entity / highlight relationship"),
+        Line::from("  Drag       - Move entity, or pan from empty space"),
+        Line::from("  Wheel      - Zoom (Shift+Wheel to pan)"),
+        Line::from(""),
         Line::from("Entity Selection:"),
         Line::from("  Tab        - Select next entity"),
         Line::from("  Shift+Tab  - Select previous entity"),
-        Line::from("  ↑↓←→       - Move selected entity"),
+        Line::from("  HJKL       - Select nearest entity left/down/up/right"),
+        Line::from("  , / .      - Previous/next column of selected entity"),
+        Line::from("  Enter      - Follow the column's foreign key"),
+        Line::from("  Backspace  - Jump back"),
+        Line::from("  Shift+↑↓←→ - Move selected entity"),
+        Line::from("  /          - Search tables and columns, jump to a match"),
+        Line::from("  e          - Show details of selected entity"),
+        Line::from("  > / <      - Widen/narrow highlighted neighborhood"),
+        Line::from("  o          - Focus on the highlighted neighborhood (Esc: back)"),
+        Line::from("  p          - Pin/unpin selected entity"),
         Line::from("  Ctrl+D/Del - Delete selected entity"),
+        Line::from("  u / Ctrl+R - Undo/redo edits, moves and layouts"),
+        Line::from("  U          - Toggle history panel"),
         Line::from(""),
         Line::from("SQL Editor (VIM Mode):"),
         Line::from("  Ctrl+S     - Sync SQL changes to diagram"),
@@ -591,7 +948,13 @@ pub fn render_help_screen(f: &mut Frame, area: Rect) {
         Line::from("  🗝         - Primary key column"),
         Line::from("  🔗         - Foreign key column"),
         Line::from("  ?          - Nullable column"),
-        Line::from("  ─│┌┐└┘►◄▲▼ - Smooth red relationship lines with corners"),
+        Line::from("  ◆          - Unique column"),
+        Line::from("  ◇          - Indexed column"),
+        Line::from("  ─│┌┐└┘     - Smooth red relationship lines with corners"),
+        Line::from("  < > ∧ ∨    - Crow's foot: many rows on this side"),
+        Line::from("  ┼          - One row on this side (doubled: exactly one)"),
+        Line::from("  ○          - Optional: zero rows allowed (nullable FK)"),
+        Line::from("  1 N 0..1   - Label notation, toggled with 't'"),
         Line::from(""),
         Line::from("Selected entities are highlighted in yellow."),
         Line::from("Smooth red relationship lines connect exact columns (FK → PK)."),
@@ -613,7 +976,14 @@ pub fn render_help_screen(f: &mut Frame, area: Rect) {
     f.render_widget(help, centered_area);
 }
 
-pub fn render_sql_editor_with_vim(f: &mut Frame, content: &str, vim_mode: crate::app::VimMode, cursor_position: usize, area: Rect) {
+pub fn render_sql_editor_with_vim(
+    f: &mut Frame,
+    content: &str,
+    vim_mode: crate::app::VimMode,
+    cursor_position: usize,
+    errors: &[crate::parser::ParseError],
+    area: Rect,
+) {
     use crate::app::VimMode;
     use ratatui::layout::Constraint;
     
@@ -624,24 +994,39 @@ pub fn render_sql_editor_with_vim(f: &mut Frame, content: &str, vim_mode: crate:
     
     let (line, col) = get_line_column_from_position(content, cursor_position);
     
-    // Split the area into content and status line
+    // Split the area into content, parse errors and status line
+    let error_height = if errors.is_empty() { 0 } else { errors.len().min(5) as u16 + 1 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(1),      // Content area
-            Constraint::Length(1),   // Status line
+            Constraint::Min(1),                 // Content area
+            Constraint::Length(error_height),   // Parse errors
+            Constraint::Length(1),              // Status line
         ])
         .split(area);
     
     // Create content with visible cursor
     let content_with_cursor = if content.is_empty() {
-        format!("Enter SQL CREATE TABLE statements here.\nPress 'i' to enter INSERT mode, ESC for NORMAL mode.\nPress Ctrl+S to parse and apply.\nPress Esc in NORMAL mode to return to diagram view.\n\n█") // Block cursor for empty
+        "Enter SQL CREATE TABLE statements here.\nPress 'i' to enter INSERT mode, ESC for NORMAL mode.\nPress Ctrl+S to parse and apply.\nPress Esc in NORMAL mode to return to diagram view.\n\n█".to_string() // Block cursor for empty
     } else {
         insert_cursor_in_content(content, cursor_position, vim_mode)
     };
 
+    // Lines that failed to parse are highlighted
+    let lines: Vec<Line> = content_with_cursor
+        .split('\n')
+        .enumerate()
+        .map(|(i, text)| {
+            if errors.iter().any(|e| e.line == i + 1) {
+                Line::styled(text.to_string(), Style::default().fg(Color::White).bg(Color::Red))
+            } else {
+                Line::from(text.to_string())
+            }
+        })
+        .collect();
+
     // Main content area
-    let content_widget = Paragraph::new(content_with_cursor.as_str())
+    let content_widget = Paragraph::new(lines)
         .block(
             Block::default()
                 .title("SQL Editor (VIM Mode)")
@@ -654,6 +1039,14 @@ pub fn render_sql_editor_with_vim(f: &mut Frame, content: &str, vim_mode: crate:
 
     f.render_widget(content_widget, chunks[0]);
 
+    if !errors.is_empty() {
+        let error_lines: Vec<Line> = errors.iter().take(5).map(|e| Line::from(e.to_string())).collect();
+        let error_widget = Paragraph::new(error_lines)
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::TOP).title(format!("{} statement(s) failed to parse", errors.len())));
+        f.render_widget(error_widget, chunks[1]);
+    }
+
     // Status line at bottom
     let status_text = format!("{} | Line: {} Col: {} | Cursor: {}", 
                              vim_mode_text, line + 1, col + 1, cursor_position);
@@ -661,7 +1054,7 @@ pub fn render_sql_editor_with_vim(f: &mut Frame, content: &str, vim_mode: crate:
         .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .block(Block::default().borders(Borders::TOP));
 
-    f.render_widget(status_widget, chunks[1]);
+    f.render_widget(status_widget, chunks[2]);
 }
 
 fn insert_cursor_in_content(content: &str, cursor_position: usize, vim_mode: crate::app::VimMode) -> String {
@@ -738,6 +1131,235 @@ pub fn render_entity_creator(f: &mut Frame, buffer: &str, area: Rect) {
     f.render_widget(text, centered_area);
 }
 
+pub fn render_search(f: &mut Frame, query: &str, matches: &[SearchMatch], cursor: usize, area: Rect) {
+    let mut lines = vec![
+        Line::from(format!("/{}_", query)),
+        Line::from(""),
+    ];
+    if matches.is_empty() && !query.trim().is_empty() {
+        lines.push(Line::styled("No matches", Style::default().fg(Color::DarkGray)));
+    }
+    for (i, search_match) in matches.iter().enumerate() {
+        let style = if i == cursor {
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else if search_match.column.is_some() {
+            Style::default().fg(Color::White)
+        } else {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        };
+        lines.push(Line::styled(search_match.label(), style));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "↑↓ choose, Enter jump, Esc cancel",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let search = Paragraph::new(lines).block(
+        Block::default()
+            .title("Search")
+            .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    let centered_area = centered_rect(50, 60, area);
+    f.render_widget(Clear, centered_area);
+    f.render_widget(search, centered_area);
+}
+
+/// Undo history down the right side of the diagram: redoable edits dimmed
+/// on top, then what `u` undoes next, then older edits.
+pub fn render_history(f: &mut Frame, history: &History, area: Rect) {
+    let mut lines: Vec<Line> = history
+        .undone()
+        .iter()
+        .map(|operation| Line::styled(format!("  {}", operation.label), Style::default().fg(Color::DarkGray)))
+        .collect();
+    for (i, operation) in history.done().iter().rev().enumerate() {
+        if i == 0 {
+            lines.push(Line::styled(
+                format!("▶ {}", operation.label),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ));
+        } else {
+            lines.push(Line::from(format!("  {}", operation.label)));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::styled("Nothing to undo", Style::default().fg(Color::DarkGray)));
+    }
+
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .title("History (u undo, Ctrl+R redo)")
+            .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    let width = area.width.min(36);
+    let panel_area = Rect::new(area.right() - width, area.y, width, area.height);
+    f.render_widget(Clear, panel_area);
+    f.render_widget(panel, panel_area);
+}
+
+pub fn render_entity_detail(f: &mut Frame, entity: &Entity, area: Rect) {
+    let label_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::styled("Columns:", label_style)];
+
+    for col in &entity.columns {
+        let mut parts = vec![format!("  {} {}", col.name, col.data_type)];
+
+        if col.is_primary_key {
+            parts.push("PRIMARY KEY".to_string());
+        }
+        if let Some(reference) = &col.references {
+            let to_column = reference.column_pairs
+                .iter()
+                .find(|pair| pair.from_column == col.name)
+                .map(|pair| pair.to_column.as_str())
+                .unwrap_or_default();
+            parts.push(format!("→ {}.{}", reference.table, to_column));
+        }
+        if !col.nullable {
+            parts.push("NOT NULL".to_string());
+        }
+        if let Some(default) = &col.default {
+            parts.push(format!("DEFAULT {}", default));
+        }
+        if let Some(generated) = &col.generated {
+            parts.push(generated.to_string());
+        }
+
+        lines.push(Line::from(parts.join(" ")));
+
+        if let Some(comment) = &col.comment {
+            lines.push(Line::styled(
+                format!("      -- {}", comment),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
+    if !entity.unique_constraints.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Unique:", label_style));
+        for unique in &entity.unique_constraints {
+            let name = unique.name.as_deref().unwrap_or("(unnamed)");
+            lines.push(Line::from(format!("  {} ({})", name, unique.columns.join(", "))));
+        }
+    }
+
+    if !entity.indexes.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Indexes:", label_style));
+        for index in &entity.indexes {
+            let mut text = format!(
+                "  {}{} ({})",
+                if index.unique { "UNIQUE " } else { "" },
+                index.name,
+                index.key_parts.join(", ")
+            );
+            if let Some(predicate) = &index.predicate {
+                text.push_str(&format!(" WHERE {}", predicate));
+            }
+            lines.push(Line::from(text));
+        }
+    }
+
+    if !entity.check_constraints.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Checks:", label_style));
+        for check in &entity.check_constraints {
+            let name = check.name.as_deref().unwrap_or("(unnamed)");
+            lines.push(Line::from(format!("  {} CHECK ({})", name, check.expression)));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from("Press Esc to return."));
+
+    let detail = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!("Entity: {}", entity.name))
+                .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .style(Style::default().fg(Color::White))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+
+    let centered_area = centered_rect(70, 80, area);
+    f.render_widget(Clear, centered_area);
+    f.render_widget(detail, centered_area);
+}
+
+impl Side {
+    /// Lines meet an entity head-on: horizontally on its left/right edges.
+    fn axis(self) -> Axis {
+        match self {
+            Side::Left | Side::Right => Axis::Horizontal,
+            Side::Top | Side::Bottom => Axis::Vertical,
+        }
+    }
+
+    /// The link from a line end into the entity it touches.
+    fn link_towards_entity(self) -> u8 {
+        match self {
+            Side::Left => LINK_RIGHT,
+            Side::Right => LINK_LEFT,
+            Side::Top => LINK_DOWN,
+            Side::Bottom => LINK_UP,
+        }
+    }
+}
+
+/// Border and line color for tables and relationships around the selection.
+fn role_color(role: Role) -> Color {
+    match role {
+        Role::Selected => Color::Yellow,
+        Role::Incoming => Color::Green,
+        Role::Outgoing => Color::LightBlue,
+    }
+}
+
+/// Box-drawing glyph for a cell linked to the given neighbours.
+fn junction_glyph(links: u8) -> &'static str {
+    match links {
+        l if l == LINK_LEFT | LINK_RIGHT | LINK_UP | LINK_DOWN => "┼",
+        l if l == LINK_LEFT | LINK_RIGHT | LINK_DOWN => "┬",
+        l if l == LINK_LEFT | LINK_RIGHT | LINK_UP => "┴",
+        l if l == LINK_UP | LINK_DOWN | LINK_RIGHT => "├",
+        l if l == LINK_UP | LINK_DOWN | LINK_LEFT => "┤",
+        l if l == LINK_RIGHT | LINK_DOWN => "┌",
+        l if l == LINK_LEFT | LINK_DOWN => "┐",
+        l if l == LINK_RIGHT | LINK_UP => "└",
+        l if l == LINK_LEFT | LINK_UP => "┘",
+        l if l & (LINK_UP | LINK_DOWN) != 0 && l & (LINK_LEFT | LINK_RIGHT) == 0 => "│",
+        _ => "─",
+    }
+}
+
+/// The cell at a screen point, if it is inside `area`.
+fn cell_in(area: Rect, (x, y): (i32, i32)) -> Option<(u16, u16)> {
+    let inside = x >= area.left() as i32 && x < area.right() as i32 && y >= area.top() as i32 && y < area.bottom() as i32;
+    inside.then_some((x as u16, y as u16))
+}
+
+fn side_of(point: (u16, u16), entity_area: &Rect) -> Side {
+    if point.0 < entity_area.x {
+        Side::Left
+    } else if point.0 >= entity_area.x + entity_area.width {
+        Side::Right
+    } else if point.1 < entity_area.y {
+        Side::Top
+    } else {
+        Side::Bottom
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -765,30 +1387,139 @@ mod tests {
 
     #[test]
     fn test_calculate_entity_area() {
-        let renderer = DiagramRenderer::new(800, 600);
+        let renderer = DiagramRenderer::new();
         
         let entity = Entity {
             name: "test".to_string(),
             columns: vec![],
-            position: Position { x: 400.0, y: 300.0 },
+            position: Position { x: 80.0, y: 48.0 },
             dimensions: Dimensions { width: 20, height: 10 },
+            ..Default::default()
         };
 
         let canvas_area = Rect {
             x: 0,
             y: 0,
-            width: 100,
-            height: 50,
+            width: 60,
+            height: 30,
         };
 
         let entity_area = renderer.calculate_entity_area(&entity, canvas_area);
         
-        assert_eq!(entity_area.x, 50);  // 400/800 * 100
-        assert_eq!(entity_area.y, 25);  // 300/600 * 50
+        assert_eq!(entity_area.x, 10);  // 80 / 8 units per column
+        assert_eq!(entity_area.y, 3);  // 48 / 16 units per row
+        // Full size, even where it does not fit a quarter of the canvas
         assert_eq!(entity_area.width, 20);
         assert_eq!(entity_area.height, 10);
     }
 
+    #[test]
+    fn test_entity_area_follows_camera() {
+        use crate::models::Column;
+
+        let mut entity = Entity {
+            name: "orders".to_string(),
+            columns: vec![
+                Column { name: "id".to_string(), data_type: "INT".to_string(), is_primary_key: true, ..Default::default() },
+                Column { name: "note".to_string(), data_type: "TEXT".to_string(), ..Default::default() },
+                Column { name: "user_id".to_string(), data_type: "INT".to_string(), is_foreign_key: true, ..Default::default() },
+            ],
+            position: Position { x: 80.0, y: 48.0 },
+            ..Default::default()
+        };
+        entity.dimensions = Dimensions::for_entity(&entity);
+        let canvas_area = Rect::new(0, 0, 60, 30);
+
+        // Panned right past the box's left edge: cut off at the view's edge
+        let mut camera = Camera::new();
+        camera.set_viewport(canvas_area);
+        camera.pan(4, 0);
+        let panned = DiagramRenderer::new().with_camera(camera).calculate_entity_area(&entity, canvas_area);
+        assert_eq!(panned.x, 0);
+        assert_eq!(panned.width, entity.dimensions.width - 6);
+        let panned_box = DiagramRenderer::new().with_camera(camera).entity_box(&entity, canvas_area);
+        assert_eq!((panned_box.x, panned_box.width), (-6, entity.dimensions.width));
+
+        // Zoomed out one step: only the key columns are listed, and the box
+        // stays within its footprint, 5 rows scaled to 3
+        let mut camera = Camera::new();
+        camera.set_viewport(canvas_area);
+        camera.zoom_out();
+        let renderer = DiagramRenderer::new().with_camera(camera);
+        assert_eq!(renderer.visible_columns(&entity).len(), 2);
+        assert_eq!(renderer.calculate_entity_area(&entity, canvas_area).height, 3);
+    }
+
+    #[test]
+    fn test_boxes_past_the_view_edge_are_cut_not_shrunk() {
+        use crate::parser::{SqlDialect, SqlParser};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql("CREATE TABLE users (id INT PRIMARY KEY, email VARCHAR(255));")
+            .unwrap();
+        schema.entities.get_mut("users").unwrap().position = Position { x: 0.0, y: 0.0 };
+
+        // Panned 10 cells right of the box's left edge
+        let mut camera = Camera::new();
+        camera.x = 80.0;
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal
+            .draw(|f| {
+                DiagramRenderer::new()
+                    .with_camera(camera)
+                    .render(f, &schema, f.area(), &None, &mut RouteCache::new(), &mut HitMap::new())
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+
+        assert!(row(0).starts_with('─'), "{}", row(0));
+        assert!(!row(0).contains("users"));
+        assert!(row(2).starts_with("ARCHAR(255)"), "{}", row(2));
+    }
+
+    #[test]
+    fn test_boxes_do_not_overlap_at_any_zoom_level() {
+        use crate::app::App;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let schema = App::new().schema;
+        let view = Rect::new(0, 0, 160, 50);
+        let mut camera = Camera::new();
+        camera.set_viewport(view);
+        camera.fit(&schema);
+        for _ in 0..10 {
+            camera.zoom_in();
+        }
+
+        loop {
+            let renderer = DiagramRenderer::new().with_camera(camera);
+            let mut terminal = Terminal::new(TestBackend::new(view.width, view.height)).unwrap();
+            terminal
+                .draw(|f| renderer.render(f, &schema, view, &None, &mut RouteCache::new(), &mut HitMap::new()))
+                .unwrap();
+
+            let boxes: Vec<(&String, Rect)> = schema
+                .entities
+                .iter()
+                .map(|(name, entity)| (name, renderer.calculate_entity_area(entity, view)))
+                .filter(|(_, area)| !area.is_empty())
+                .collect();
+            for (i, (a, a_area)) in boxes.iter().enumerate() {
+                for (b, b_area) in &boxes[i + 1..] {
+                    assert!(!a_area.intersects(*b_area), "{} overlaps {} at scale {}", a, b, camera.scale());
+                }
+            }
+
+            let scale = camera.scale();
+            camera.zoom_out();
+            if camera.scale() == scale {
+                break;
+            }
+        }
+    }
+
     #[test]
     fn test_centered_rect() {
         let area = Rect {
@@ -810,7 +1541,7 @@ mod tests {
     fn test_calculate_column_y_position() {
         use crate::models::Column;
         
-        let renderer = DiagramRenderer::new(800, 600);
+        let renderer = DiagramRenderer::new();
         
         let entity = Entity {
             name: "test_table".to_string(),
@@ -822,6 +1553,7 @@ mod tests {
                     is_primary_key: true,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
                 Column {
                     name: "user_id".to_string(),
@@ -830,6 +1562,7 @@ mod tests {
                     is_primary_key: false,
                     is_foreign_key: true,
                     references: None,
+                    ..Default::default()
                 },
                 Column {
                     name: "name".to_string(),
@@ -838,13 +1571,15 @@ mod tests {
                     is_primary_key: false,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
             ],
             position: Position { x: 100.0, y: 100.0 },
             dimensions: Dimensions { width: 30, height: 10 },
+            ..Default::default()
         };
 
-        let entity_area = Rect {
+        let entity_area = EntityBox {
             x: 10,
             y: 10,
             width: 30,
@@ -913,18 +1648,18 @@ mod tests {
 
     #[test] 
     fn test_direct_line_edge_cases() {
-        let _renderer = DiagramRenderer::new(800, 600);
+        let _renderer = DiagramRenderer::new();
         
         // Test that pure horizontal lines don't create corner artifacts
         // This would previously create unwanted ┼ characters
         
         // Pure horizontal case (dy = 0) 
-        let from_horizontal = (10, 20);
-        let to_horizontal = (50, 20); // Same Y coordinate
+        let from_horizontal: (u16, u16) = (10, 20);
+        let to_horizontal: (u16, u16) = (50, 20); // Same Y coordinate
         
         // Pure vertical case (dx = 0)
-        let from_vertical = (30, 10);  
-        let to_vertical = (30, 40);   // Same X coordinate
+        let from_vertical: (u16, u16) = (30, 10);  
+        let to_vertical: (u16, u16) = (30, 40);   // Same X coordinate
         
         // These should not panic and should use direct line drawing
         // The test passes if the methods execute without errors
@@ -947,7 +1682,7 @@ mod tests {
     fn test_entity_penetration_avoidance() {
         use crate::models::Column;
         
-        let renderer = DiagramRenderer::new(800, 600);
+        let renderer = DiagramRenderer::new();
         
         // Create a test entity that would block line segments
         let _blocking_entity = Entity {
@@ -960,10 +1695,12 @@ mod tests {
                     is_primary_key: true,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 }
             ],
             position: Position { x: 50.0, y: 50.0 },
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         
         // Create entity area that would be in the path of a line
@@ -994,4 +1731,142 @@ mod tests {
         assert!(!renderer.point_is_inside_entity(15, 9, &entity_area));  // Just above entity
         assert!(!renderer.point_is_inside_entity(15, 15, &entity_area)); // Just below entity
     }
-}
\ No newline at end of file
+
+    fn render_to_text(notation: RelationshipNotation, optional: bool) -> String {
+        use crate::parser::{SqlDialect, SqlParser};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let nullability = if optional { "" } else { " NOT NULL" };
+        let sql = format!(
+            "CREATE TABLE users (id INT PRIMARY KEY); CREATE TABLE posts (id INT PRIMARY KEY, user_id INT{} REFERENCES users(id));",
+            nullability
+        );
+        let mut schema = SqlParser::new(SqlDialect::Generic).parse_sql(&sql).unwrap();
+        schema.entities.get_mut("posts").unwrap().position = Position { x: 0.0, y: 0.0 };
+        schema.entities.get_mut("users").unwrap().position = Position { x: 400.0, y: 0.0 };
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|f| DiagramRenderer::new().with_notation(notation).render(f, &schema, f.area(), &None, &mut RouteCache::new(), &mut HitMap::new()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_minimap_shows_diagram_and_view() {
+        use crate::parser::{SqlDialect, SqlParser};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql("CREATE TABLE users (id INT PRIMARY KEY); CREATE TABLE posts (id INT PRIMARY KEY);")
+            .unwrap();
+        let render = |show_minimap: bool| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal
+                .draw(|f| {
+                    DiagramRenderer::new()
+                        .with_minimap(show_minimap)
+                        .render(f, &schema, f.area(), &Some("users".to_string()), &mut RouteCache::new(), &mut HitMap::new())
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            // Bottom-right corner, where the minimap goes
+            (20..30)
+                .flat_map(|y| (60..100).map(move |x| (x, y)))
+                .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+
+        let corner = render(true);
+        assert!(corner.contains("Map"));
+        assert!(corner.chars().any(|c| ('\u{2801}'..='\u{28FF}').contains(&c)));
+        assert!(!render(false).contains("Map"));
+    }
+
+    #[test]
+    fn test_history_panel_stacks_above_minimap() {
+        use crate::parser::{SqlDialect, SqlParser};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql("CREATE TABLE users (id INT PRIMARY KEY);")
+            .unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                DiagramRenderer::new()
+                    .with_minimap(true)
+                    .render(f, &schema, area, &None, &mut RouteCache::new(), &mut HitMap::new());
+                render_history(f, &History::new(), above_minimap(area));
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = (0..30)
+            .flat_map(|y| (60..100).map(move |x| (x, y)))
+            .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+            .collect();
+        assert!(text.contains("History"));
+        assert!(text.contains("Map"));
+    }
+
+    #[test]
+    fn test_neighborhood_colors_and_dims() {
+        use crate::parser::{SqlDialect, SqlParser};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql(
+                "CREATE TABLE users (id INT PRIMARY KEY);
+                 CREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));
+                 CREATE TABLE tags (id INT PRIMARY KEY);",
+            )
+            .unwrap();
+        for (name, x, y) in [("posts", 0.0, 0.0), ("users", 400.0, 0.0), ("tags", 0.0, 320.0)] {
+            schema.entities.get_mut(name).unwrap().position = Position { x, y };
+        }
+
+        let corner_colors = |hops: usize| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+            terminal
+                .draw(|f| {
+                    DiagramRenderer::new()
+                        .with_neighborhood_hops(hops)
+                        .render(f, &schema, f.area(), &Some("users".to_string()), &mut RouteCache::new(), &mut HitMap::new())
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            // Top-left border cell of posts, users and tags
+            [(0, 0), (50, 0), (0, 20)].map(|(x, y)| buffer[(x, y)].fg)
+        };
+
+        assert_eq!(corner_colors(1), [Color::Green, Color::Yellow, Color::DarkGray]);
+        assert_eq!(corner_colors(0), [Color::White, Color::Yellow, Color::White]);
+    }
+
+    #[test]
+    fn test_crows_foot_ends() {
+        // posts (many, optional side) ──> users (exactly one)
+        let text = render_to_text(RelationshipNotation::CrowsFoot, false);
+        assert!(text.contains(">○"));
+        assert!(text.contains("┼┼"));
+
+        let text = render_to_text(RelationshipNotation::CrowsFoot, true);
+        assert!(text.contains("○┼"));
+        assert!(!text.contains("┼┼"));
+    }
+
+    #[test]
+    fn test_label_notation_fallback() {
+        let text = render_to_text(RelationshipNotation::Labels, true);
+        assert!(text.contains("N"));
+        assert!(text.contains("0..1"));
+        assert!(!text.contains("○"));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::models::Schema;
+use std::collections::{HashMap, HashSet};
+
+/// How a table is related to the selected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Selected,
+    /// Reached through a foreign key pointing at the table before it
+    Incoming,
+    /// Reached through a foreign key of the table before it
+    Outgoing,
+}
+
+/// Tables and relationships within a number of hops of the selected table.
+#[derive(Debug, Default)]
+pub struct Neighborhood {
+    pub entities: HashMap<String, Role>,
+    /// Relationship indices, with the role of the table each one leads to
+    pub relationships: HashMap<usize, Role>,
+}
+
+impl Neighborhood {
+    /// Breadth-first over the relationships in both directions. `None` when
+    /// nothing is selected or the highlight is off.
+    pub fn around(schema: &Schema, selected: &str, hops: usize) -> Option<Self> {
+        if hops == 0 || !schema.entities.contains_key(selected) {
+            return None;
+        }
+
+        let mut neighborhood = Self::default();
+        neighborhood.entities.insert(selected.to_string(), Role::Selected);
+        let mut frontier = vec![selected.to_string()];
+
+        for _ in 0..hops {
+            let current: HashSet<&str> = frontier.iter().map(String::as_str).collect();
+            let mut next = Vec::new();
+
+            for (index, relationship) in schema.relationships.iter().enumerate() {
+                let reached = if current.contains(relationship.to_table.as_str()) {
+                    Some((&relationship.from_table, Role::Incoming))
+                } else if current.contains(relationship.from_table.as_str()) {
+                    Some((&relationship.to_table, Role::Outgoing))
+                } else {
+                    None
+                };
+                let Some((table, role)) = reached else {
+                    continue;
+                };
+
+                // A table keeps the role of the first path that reached it
+                let role = *neighborhood.entities.entry(table.clone()).or_insert_with(|| {
+                    next.push(table.clone());
+                    role
+                });
+                neighborhood.relationships.entry(index).or_insert(role);
+            }
+            frontier = next;
+        }
+
+        // Relationships between tables that are both in, such as two tables
+        // one hop out that also reference each other
+        for (index, relationship) in schema.relationships.iter().enumerate() {
+            if neighborhood.entities.contains_key(&relationship.from_table)
+                && let Some(&role) = neighborhood.entities.get(&relationship.to_table)
+            {
+                neighborhood.relationships.entry(index).or_insert(role);
+            }
+        }
+
+        Some(neighborhood)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{SqlDialect, SqlParser};
+
+    #[test]
+    fn test_hops_widen_the_neighborhood() {
+        let schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql(
+                "CREATE TABLE users (id INT PRIMARY KEY);
+                 CREATE TABLE orders (id INT PRIMARY KEY, user_id INT REFERENCES users(id));
+                 CREATE TABLE items (id INT PRIMARY KEY, order_id INT REFERENCES orders(id));
+                 CREATE TABLE tags (id INT PRIMARY KEY);",
+            )
+            .unwrap();
+
+        let one = Neighborhood::around(&schema, "orders", 1).unwrap();
+        assert_eq!(one.entities["orders"], Role::Selected);
+        assert_eq!(one.entities["users"], Role::Outgoing);
+        assert_eq!(one.entities["items"], Role::Incoming);
+        assert!(!one.entities.contains_key("tags"));
+        assert_eq!(one.relationships.len(), 2);
+
+        let one = Neighborhood::around(&schema, "items", 1).unwrap();
+        assert!(!one.entities.contains_key("users"));
+        let two = Neighborhood::around(&schema, "items", 2).unwrap();
+        assert_eq!(two.entities["users"], Role::Outgoing);
+
+        assert!(Neighborhood::around(&schema, "items", 0).is_none());
+    }
+
+    #[test]
+    fn test_relationships_between_tables_in_the_neighborhood() {
+        // reviews -> users and reviews -> products are both one hop from users
+        let schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql(
+                "CREATE TABLE products (id INT PRIMARY KEY);
+                 CREATE TABLE users (id INT PRIMARY KEY, favorite_id INT REFERENCES products(id));
+                 CREATE TABLE reviews (id INT PRIMARY KEY, user_id INT REFERENCES users(id), product_id INT REFERENCES products(id));",
+            )
+            .unwrap();
+
+        let one = Neighborhood::around(&schema, "users", 1).unwrap();
+        assert_eq!(one.entities.len(), 3);
+        assert_eq!(one.relationships.len(), 3);
+        let lateral = schema
+            .relationships
+            .iter()
+            .position(|r| r.from_table == "reviews" && r.to_table == "products")
+            .unwrap();
+        assert_eq!(one.relationships[&lateral], Role::Outgoing);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::models::Schema;
+
+/// Most matches listed under the search prompt.
+const MAX_MATCHES: usize = 20;
+
+/// A table, or a column of one, matching the search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub entity: String,
+    pub column: Option<String>,
+    score: i64,
+}
+
+impl SearchMatch {
+    /// `table` or `table.column`, as listed under the prompt.
+    pub fn label(&self) -> String {
+        match &self.column {
+            Some(column) => format!("{}.{}", self.entity, column),
+            None => self.entity.clone(),
+        }
+    }
+}
+
+/// Tables and columns whose names contain the query's characters in order,
+/// best first. Columns also match against `table.column`, so `ord.user`
+/// finds `orders.user_id`. Ties keep declaration order, tables before
+/// their columns.
+pub fn search(schema: &Schema, query: &str) -> Vec<SearchMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for (name, entity) in &schema.entities {
+        if let Some(score) = fuzzy_score(query, name) {
+            matches.push(SearchMatch { entity: name.clone(), column: None, score });
+        }
+        for column in &entity.columns {
+            let qualified = format!("{}.{}", name, column.name);
+            let score = fuzzy_score(query, &column.name).max(fuzzy_score(query, &qualified));
+            if let Some(score) = score {
+                matches.push(SearchMatch { entity: name.clone(), column: Some(column.name.clone()), score });
+            }
+        }
+    }
+
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches.truncate(MAX_MATCHES);
+    matches
+}
+
+/// Score of `candidate` for `query`, ignoring case, or `None` when the
+/// query's characters do not all appear in order. Runs of consecutive
+/// characters and matches at the start of a word count extra; characters
+/// skipped in between cost a little.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        let found = next + candidate[next..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], '_' | '.' | ' ') {
+            score += 8;
+        }
+        score -= (found - next) as i64;
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{SqlDialect, SqlParser};
+
+    #[test]
+    fn test_fuzzy_score_prefers_runs_and_word_starts() {
+        assert!(fuzzy_score("usr", "users").is_some());
+        assert!(fuzzy_score("sru", "users").is_none());
+        assert!(fuzzy_score("USE", "users") > fuzzy_score("use", "product_uses"));
+        assert!(fuzzy_score("ui", "user_id") > fuzzy_score("ui", "quick_fix"));
+    }
+
+    #[test]
+    fn test_search_tables_and_columns() {
+        let schema = SqlParser::new(SqlDialect::Generic)
+            .parse_sql(
+                "CREATE TABLE users (id INT PRIMARY KEY, email TEXT);
+                 CREATE TABLE orders (id INT PRIMARY KEY, user_id INT REFERENCES users(id));",
+            )
+            .unwrap();
+
+        let matches = search(&schema, "ord.user");
+        assert_eq!(matches[0].label(), "orders.user_id");
+
+        let matches = search(&schema, "users");
+        assert_eq!(matches[0].label(), "users");
+
+        let matches = search(&schema, "mail");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].column.as_deref(), Some("email"));
+
+        assert!(search(&schema, "  ").is_empty());
+    }
+}
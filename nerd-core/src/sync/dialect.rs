@@ -0,0 +1,197 @@
+use crate::parser::SqlDialect;
+
+/// Words that cannot be used as bare identifiers in the dialects that only
+/// quote when they have to.
+const RESERVED_WORDS: &[&str] = &[
+    "ALL", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CHECK", "COLUMN", "CONSTRAINT", "CREATE",
+    "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE", "END", "EXISTS", "FOREIGN", "FROM",
+    "GROUP", "HAVING", "IN", "INDEX", "INSERT", "INTO", "IS", "JOIN", "KEY", "LIKE", "LIMIT", "NOT",
+    "NULL", "ON", "OR", "ORDER", "PRIMARY", "REFERENCES", "SELECT", "SET", "TABLE", "THEN", "TO",
+    "UNION", "UNIQUE", "UPDATE", "USER", "USING", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+/// Quote a (possibly schema-qualified) identifier for the target dialect.
+/// MySQL and SQL Server dumps always quote; Postgres and SQLite only quote
+/// names that would otherwise be read differently. Generic output is left
+/// bare.
+pub(super) fn quote_ident(dialect: SqlDialect, name: &str) -> String {
+    name.split('.')
+        .map(|part| quote_part(dialect, part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Quote a name that is never schema-qualified, such as a constraint name,
+/// as a single identifier.
+pub(super) fn quote_part(dialect: SqlDialect, part: &str) -> String {
+    match dialect {
+        SqlDialect::MySql => format!("`{}`", part.replace('`', "``")),
+        SqlDialect::MsSql => format!("[{}]", part.replace(']', "]]")),
+        SqlDialect::PostgreSql | SqlDialect::Sqlite if needs_quotes(dialect, part) => {
+            format!("\"{}\"", part.replace('"', "\"\""))
+        }
+        _ => part.to_string(),
+    }
+}
+
+fn needs_quotes(dialect: SqlDialect, part: &str) -> bool {
+    // Postgres folds bare names to lower case, so anything else must be quoted
+    let plain_char = |c: char| match dialect {
+        SqlDialect::PostgreSql => c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_',
+        _ => c.is_ascii_alphanumeric() || c == '_',
+    };
+    let starts_plain = part.chars().next().is_some_and(|c| !c.is_ascii_digit() && plain_char(c));
+
+    !starts_plain
+        || !part.chars().all(plain_char)
+        || RESERVED_WORDS.contains(&part.to_uppercase().as_str())
+}
+
+/// Quote the column in an index key part such as `created_at DESC`.
+/// Expression key parts are kept verbatim.
+pub(super) fn quote_key_part(dialect: SqlDialect, key_part: &str) -> String {
+    if key_part.contains('(') {
+        return key_part.to_string();
+    }
+    match key_part.split_once(char::is_whitespace) {
+        Some((column, order)) => format!("{} {}", quote_ident(dialect, column), order),
+        None => quote_ident(dialect, key_part),
+    }
+}
+
+/// Translate a parsed type name into the closest type the target dialect
+/// understands, keeping length/precision arguments where they still apply.
+pub(super) fn map_data_type(dialect: SqlDialect, data_type: &str) -> String {
+    let unsigned = data_type.to_uppercase().contains(" UNSIGNED");
+    let data_type = if unsigned && dialect != SqlDialect::MySql {
+        data_type.replace(" UNSIGNED", "").replace(" unsigned", "")
+    } else {
+        data_type.to_string()
+    };
+    let (base, args) = match data_type.find('(') {
+        Some(i) => (data_type[..i].trim().to_uppercase(), &data_type[i..]),
+        None => (data_type.trim().to_uppercase(), ""),
+    };
+    let is_integer = matches!(base.as_str(), "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "INTEGER" | "BIGINT");
+
+    let mapped = match dialect {
+        SqlDialect::MySql => match base.as_str() {
+            "BOOLEAN" | "BOOL" => "TINYINT(1)",
+            "UUID" => "CHAR(36)",
+            "BYTEA" => "BLOB",
+            "DOUBLE PRECISION" => "DOUBLE",
+            "TIMESTAMPTZ" => "TIMESTAMP",
+            "JSONB" => "JSON",
+            "NVARCHAR" => return format!("VARCHAR{}", args),
+            _ => return data_type,
+        },
+        SqlDialect::PostgreSql => match base.as_str() {
+            "TINYINT" => "SMALLINT",
+            "MEDIUMINT" => "INT",
+            _ if is_integer => return base,
+            "DOUBLE" => "DOUBLE PRECISION",
+            "DATETIME" => "TIMESTAMP",
+            "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => "BYTEA",
+            "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT" => "TEXT",
+            "NVARCHAR" => return format!("VARCHAR{}", args),
+            _ => return data_type,
+        },
+        // SQLite only cares about type affinity, and AUTOINCREMENT needs INTEGER
+        SqlDialect::Sqlite => match base.as_str() {
+            _ if is_integer => "INTEGER",
+            "BOOLEAN" | "BOOL" => "INTEGER",
+            "CHAR" | "VARCHAR" | "NVARCHAR" | "TEXT" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT" | "UUID" | "JSON" | "JSONB" => "TEXT",
+            "FLOAT" | "DOUBLE" | "DOUBLE PRECISION" | "REAL" => "REAL",
+            "BYTEA" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => "BLOB",
+            _ => return data_type,
+        },
+        SqlDialect::MsSql => match base.as_str() {
+            "MEDIUMINT" => "INT",
+            _ if is_integer => return base,
+            "BOOLEAN" | "BOOL" => "BIT",
+            "TEXT" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT" | "JSON" | "JSONB" => "NVARCHAR(MAX)",
+            // SQL Server's TIMESTAMP is a row version, not a point in time
+            "TIMESTAMP" | "DATETIME" | "TIMESTAMPTZ" => "DATETIME2",
+            "UUID" => "UNIQUEIDENTIFIER",
+            "BLOB" | "BYTEA" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => "VARBINARY(MAX)",
+            "DOUBLE" | "DOUBLE PRECISION" => "FLOAT",
+            _ => return data_type,
+        },
+        SqlDialect::Generic | SqlDialect::Snowflake => return data_type,
+    };
+
+    mapped.to_string()
+}
+
+/// Translate a column default for the target dialect. Boolean literals
+/// become `1`/`0` where booleans are stored as bits: SQL Server's `BIT`
+/// rejects `true`, and MySQL's `TINYINT(1)` is a number.
+pub(super) fn map_default(dialect: SqlDialect, data_type: &str, default: &str) -> String {
+    let bit = match default.trim().to_uppercase().as_str() {
+        "TRUE" => Some("1"),
+        "FALSE" => Some("0"),
+        _ => None,
+    };
+    match (dialect, bit) {
+        (SqlDialect::MsSql, Some(bit)) => bit.to_string(),
+        (SqlDialect::MySql, Some(bit)) if map_data_type(dialect, data_type) == "TINYINT(1)" => bit.to_string(),
+        _ => default.to_string(),
+    }
+}
+
+/// Pull `(seed, increment)` out of identity options such as
+/// `INCREMENT BY 1 START WITH 100`.
+pub(super) fn identity_seed_and_increment(sequence_options: Option<&str>) -> (String, String) {
+    let mut seed = "1".to_string();
+    let mut increment = "1".to_string();
+    if let Some(options) = sequence_options {
+        let words: Vec<&str> = options.split_whitespace().collect();
+        for window in words.windows(3) {
+            match (window[0].to_uppercase().as_str(), window[1].to_uppercase().as_str()) {
+                ("START", "WITH") => seed = window[2].to_string(),
+                ("INCREMENT", "BY") => increment = window[2].to_string(),
+                _ => {}
+            }
+        }
+    }
+    (seed, increment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident(SqlDialect::Generic, "users"), "users");
+        assert_eq!(quote_ident(SqlDialect::MySql, "users"), "`users`");
+        assert_eq!(quote_ident(SqlDialect::MsSql, "dbo.users"), "[dbo].[users]");
+        assert_eq!(quote_ident(SqlDialect::PostgreSql, "users"), "users");
+        assert_eq!(quote_ident(SqlDialect::PostgreSql, "User"), "\"User\"");
+        assert_eq!(quote_ident(SqlDialect::PostgreSql, "order"), "\"order\"");
+        assert_eq!(quote_ident(SqlDialect::Sqlite, "OrderItems"), "OrderItems");
+        assert_eq!(quote_ident(SqlDialect::Sqlite, "line item"), "\"line item\"");
+    }
+
+    #[test]
+    fn test_map_data_type() {
+        assert_eq!(map_data_type(SqlDialect::MySql, "BOOLEAN"), "TINYINT(1)");
+        assert_eq!(map_data_type(SqlDialect::MySql, "INT UNSIGNED"), "INT UNSIGNED");
+        assert_eq!(map_data_type(SqlDialect::PostgreSql, "INT(11) UNSIGNED"), "INT");
+        assert_eq!(map_data_type(SqlDialect::PostgreSql, "DOUBLE"), "DOUBLE PRECISION");
+        assert_eq!(map_data_type(SqlDialect::Sqlite, "VARCHAR(100)"), "TEXT");
+        assert_eq!(map_data_type(SqlDialect::MsSql, "BOOLEAN"), "BIT");
+        assert_eq!(map_data_type(SqlDialect::MsSql, "DECIMAL(10,2)"), "DECIMAL(10,2)");
+        assert_eq!(map_data_type(SqlDialect::Generic, "BOOLEAN"), "BOOLEAN");
+    }
+
+    #[test]
+    fn test_map_default() {
+        assert_eq!(map_default(SqlDialect::MsSql, "BOOLEAN", "true"), "1");
+        assert_eq!(map_default(SqlDialect::MsSql, "BIT", "FALSE"), "0");
+        assert_eq!(map_default(SqlDialect::MySql, "BOOLEAN", "false"), "0");
+        assert_eq!(map_default(SqlDialect::MySql, "VARCHAR(5)", "'true'"), "'true'");
+        assert_eq!(map_default(SqlDialect::PostgreSql, "BOOLEAN", "true"), "true");
+        assert_eq!(map_default(SqlDialect::MsSql, "INT", "0"), "0");
+    }
+}
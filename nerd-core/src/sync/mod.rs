@@ -1,6 +1,11 @@
-use crate::models::{Schema, Entity, Column, Relationship};
-use anyhow::Result;
+use crate::models::{Schema, Entity, Column, GeneratedColumn, Index, Relationship};
+use crate::parser::{ParseError, SqlDialect};
 
+mod dialect;
+
+use dialect::{identity_seed_and_increment, map_data_type, map_default, quote_ident, quote_key_part, quote_part};
+
+#[derive(Default)]
 pub struct SchemaSync {
 }
 
@@ -9,78 +14,247 @@ impl SchemaSync {
         Self {}
     }
 
-    /// Generate SQL DDL from the current schema
-    pub fn generate_sql(&self, schema: &Schema) -> String {
+    /// Generate SQL DDL from the current schema in the given dialect
+    pub fn generate_sql(&self, schema: &Schema, dialect: SqlDialect) -> String {
         let mut sql_statements = Vec::new();
 
         // Generate CREATE TABLE statements
-        for (_table_name, entity) in &schema.entities {
-            let table_sql = self.generate_create_table(entity);
+        for entity in schema.entities.values() {
+            // SQLite cannot add foreign keys after the fact, so they go inline
+            let inline_foreign_keys: Vec<&Relationship> = if dialect == SqlDialect::Sqlite {
+                schema.relationships.iter().filter(|r| r.from_table == entity.name).collect()
+            } else {
+                Vec::new()
+            };
+            let table_sql = self.generate_create_table(entity, &inline_foreign_keys, dialect);
             sql_statements.push(table_sql);
         }
 
+        // Postgres keeps column comments in separate statements
+        if dialect == SqlDialect::PostgreSql {
+            for entity in schema.entities.values() {
+                for col in &entity.columns {
+                    if let Some(comment) = &col.comment {
+                        sql_statements.push(format!(
+                            "COMMENT ON COLUMN {}.{} IS '{}';",
+                            quote_ident(dialect, &entity.name),
+                            quote_ident(dialect, &col.name),
+                            comment.replace('\'', "''")
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Generate CREATE INDEX statements
+        for entity in schema.entities.values() {
+            for index in &entity.indexes {
+                sql_statements.push(self.generate_create_index(&entity.name, index, dialect));
+            }
+        }
+
         // Generate ALTER TABLE statements for foreign keys
-        for relationship in &schema.relationships {
-            let fk_sql = self.generate_foreign_key_constraint(relationship);
-            sql_statements.push(fk_sql);
+        if dialect != SqlDialect::Sqlite {
+            for relationship in &schema.relationships {
+                let fk_sql = self.generate_foreign_key_constraint(relationship, dialect);
+                sql_statements.push(fk_sql);
+            }
         }
 
         sql_statements.join("\n\n")
     }
 
-    fn generate_create_table(&self, entity: &Entity) -> String {
+    fn generate_create_table(&self, entity: &Entity, inline_foreign_keys: &[&Relationship], dialect: SqlDialect) -> String {
         let mut lines = Vec::new();
-        lines.push(format!("CREATE TABLE {} (", entity.name));
+        lines.push(format!("CREATE TABLE {} (", quote_ident(dialect, &entity.name)));
+
+        // A composite key has to be declared once for the whole table
+        let primary_keys: Vec<&Column> = entity.columns.iter().filter(|col| col.is_primary_key).collect();
+        let inline_primary_key = primary_keys.len() == 1;
 
-        let column_definitions: Vec<String> = entity.columns.iter().map(|col| {
+        let mut definitions: Vec<String> = entity.columns.iter().map(|col| {
             let mut parts = Vec::new();
-            parts.push(format!("    {}", col.name));
-            parts.push(col.data_type.clone());
+            parts.push(format!("    {}", quote_ident(dialect, &col.name)));
+            parts.push(map_data_type(dialect, &col.data_type));
+
+            let sqlite_autoincrement = dialect == SqlDialect::Sqlite
+                && matches!(col.generated, Some(GeneratedColumn::Identity { .. }));
+
+            if let Some(generated) = &col.generated {
+                parts.extend(self.generated_clause(generated, dialect));
+            }
+
+            if let Some(default) = &col.default {
+                parts.push(format!("DEFAULT {}", map_default(dialect, &col.data_type, default)));
+            }
+
+            if let Some(on_update) = &col.on_update
+                && matches!(dialect, SqlDialect::Generic | SqlDialect::MySql)
+            {
+                parts.push(format!("ON UPDATE {}", on_update));
+            }
             
             if !col.nullable {
                 parts.push("NOT NULL".to_string());
             }
             
-            if col.is_primary_key {
+            if col.is_primary_key && inline_primary_key {
                 parts.push("PRIMARY KEY".to_string());
+                if sqlite_autoincrement {
+                    parts.push("AUTOINCREMENT".to_string());
+                }
+            }
+
+            if let Some(comment) = &col.comment {
+                match dialect {
+                    SqlDialect::Generic | SqlDialect::MySql | SqlDialect::Snowflake => {
+                        parts.push(format!("COMMENT '{}'", comment.replace('\'', "''")));
+                    }
+                    SqlDialect::Sqlite | SqlDialect::MsSql => {
+                        parts.push(format!("/* {} */", comment.replace("*/", "* /")));
+                    }
+                    SqlDialect::PostgreSql => {}
+                }
             }
 
             parts.join(" ")
         }).collect();
 
-        lines.push(column_definitions.join(",\n"));
-        lines.push(");".to_string());
+        if primary_keys.len() > 1 {
+            let columns: Vec<String> = primary_keys.iter().map(|col| quote_ident(dialect, &col.name)).collect();
+            definitions.push(format!("    PRIMARY KEY ({})", columns.join(", ")));
+        }
+
+        for unique in &entity.unique_constraints {
+            definitions.push(format!(
+                "    {}UNIQUE ({})",
+                self.constraint_prefix(&unique.name, dialect),
+                self.column_list(unique.columns.iter().map(String::as_str), dialect)
+            ));
+        }
+
+        for check in &entity.check_constraints {
+            definitions.push(format!(
+                "    {}CHECK ({})",
+                self.constraint_prefix(&check.name, dialect),
+                check.expression
+            ));
+        }
+
+        for relationship in inline_foreign_keys {
+            definitions.push(format!("    {}", self.foreign_key_clause(relationship, dialect)));
+        }
+
+        lines.push(definitions.join(",\n"));
+        lines.push(format!("){};", self.table_options(entity, dialect)));
 
         lines.join("\n")
     }
 
-    fn generate_foreign_key_constraint(&self, relationship: &Relationship) -> String {
-        let constraint_name = format!("fk_{}_{}", relationship.from_table, relationship.from_column);
-        format!(
-            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({});",
-            relationship.from_table,
-            constraint_name,
-            relationship.from_column,
-            relationship.to_table,
-            relationship.to_column
-        )
+    fn generated_clause(&self, generated: &GeneratedColumn, dialect: SqlDialect) -> Option<String> {
+        match (generated, dialect) {
+            (GeneratedColumn::Identity { .. }, SqlDialect::MySql) => Some("AUTO_INCREMENT".to_string()),
+            // Written as PRIMARY KEY AUTOINCREMENT, which only works on the key itself
+            (GeneratedColumn::Identity { .. }, SqlDialect::Sqlite) => None,
+            (GeneratedColumn::Identity { sequence_options, .. }, SqlDialect::MsSql) => {
+                let (seed, increment) = identity_seed_and_increment(sequence_options.as_deref());
+                Some(format!("IDENTITY({}, {})", seed, increment))
+            }
+            (GeneratedColumn::Computed { expression, stored }, SqlDialect::MsSql) => {
+                Some(format!("AS ({}){}", expression, if *stored { " PERSISTED" } else { "" }))
+            }
+            _ => Some(generated.to_string()),
+        }
     }
 
-    /// Merge changes from SQL back into the schema
-    pub fn merge_sql_changes(&self, current_schema: &mut Schema, sql_content: &str) -> Result<bool> {
-        let parser = crate::parser::SqlParser::new();
-        match parser.parse_sql(sql_content) {
-            Ok(new_schema) => {
-                let has_changes = self.detect_changes(current_schema, &new_schema);
-                if has_changes {
-                    self.apply_changes(current_schema, new_schema);
-                    Ok(true)
-                } else {
-                    Ok(false)
+    fn table_options(&self, entity: &Entity, dialect: SqlDialect) -> String {
+        let options = &entity.options;
+        let mut parts = Vec::new();
+        match dialect {
+            SqlDialect::MySql => {
+                if let Some(engine) = &options.engine {
+                    parts.push(format!("ENGINE={}", engine));
+                }
+                if let Some(charset) = &options.charset {
+                    parts.push(format!("DEFAULT CHARSET={}", charset));
+                }
+                if let Some(collation) = &options.collation {
+                    parts.push(format!("COLLATE={}", collation));
                 }
             }
-            Err(e) => Err(e),
+            SqlDialect::Sqlite if options.without_rowid => parts.push("WITHOUT ROWID".to_string()),
+            _ => {}
+        }
+
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", parts.join(" "))
+        }
+    }
+
+    fn column_list<'a>(&self, columns: impl Iterator<Item = &'a str>, dialect: SqlDialect) -> String {
+        columns.map(|column| quote_ident(dialect, column)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn constraint_prefix(&self, name: &Option<String>, dialect: SqlDialect) -> String {
+        match name {
+            Some(name) => format!("CONSTRAINT {} ", quote_part(dialect, name)),
+            None => String::new(),
+        }
+    }
+
+    fn generate_create_index(&self, table_name: &str, index: &Index, dialect: SqlDialect) -> String {
+        let key_parts: Vec<String> = index.key_parts.iter().map(|part| quote_key_part(dialect, part)).collect();
+        let mut sql = format!(
+            "CREATE {}INDEX {} ON {} ({})",
+            if index.unique { "UNIQUE " } else { "" },
+            quote_ident(dialect, &index.name),
+            quote_ident(dialect, table_name),
+            key_parts.join(", ")
+        );
+
+        if let Some(predicate) = &index.predicate {
+            if dialect == SqlDialect::MySql {
+                // MySQL has no partial indexes; keep the intent visible
+                sql.push_str(&format!(" /* WHERE {} */", predicate.replace("*/", "* /")));
+            } else {
+                sql.push_str(&format!(" WHERE {}", predicate));
+            }
+        }
+
+        sql.push(';');
+        sql
+    }
+
+    fn foreign_key_clause(&self, relationship: &Relationship, dialect: SqlDialect) -> String {
+        format!(
+            "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+            quote_part(dialect, &relationship.constraint_name()),
+            self.column_list(relationship.local_columns(), dialect),
+            quote_ident(dialect, &relationship.to_table),
+            self.column_list(relationship.referenced_columns(), dialect)
+        )
+    }
+
+    fn generate_foreign_key_constraint(&self, relationship: &Relationship, dialect: SqlDialect) -> String {
+        format!(
+            "ALTER TABLE {} ADD {};",
+            quote_ident(dialect, &relationship.from_table),
+            self.foreign_key_clause(relationship, dialect)
+        )
+    }
+
+    /// Merge changes from SQL back into the schema. Statements that fail to
+    /// parse are skipped and returned; the rest are still merged.
+    pub fn merge_sql_changes(&self, current_schema: &mut Schema, sql_content: &str, dialect: SqlDialect) -> (bool, Vec<ParseError>) {
+        let parser = crate::parser::SqlParser::new(dialect);
+        let (new_schema, errors) = parser.parse_sql_lenient(sql_content);
+        let has_changes = self.detect_changes(current_schema, &new_schema);
+        if has_changes {
+            self.apply_changes(current_schema, new_schema);
         }
+        (has_changes, errors)
     }
 
     fn detect_changes(&self, current: &Schema, new: &Schema) -> bool {
@@ -121,6 +295,14 @@ impl SchemaSync {
             return true;
         }
 
+        if current.unique_constraints != new.unique_constraints
+            || current.indexes != new.indexes
+            || current.check_constraints != new.check_constraints
+            || current.options != new.options
+        {
+            return true;
+        }
+
         for (i, new_col) in new.columns.iter().enumerate() {
             if let Some(current_col) = current.columns.get(i) {
                 if self.column_changed(current_col, new_col) {
@@ -140,28 +322,32 @@ impl SchemaSync {
         current.nullable != new.nullable ||
         current.is_primary_key != new.is_primary_key ||
         current.is_foreign_key != new.is_foreign_key ||
-        current.references != new.references
+        current.references != new.references ||
+        current.default != new.default ||
+        current.generated != new.generated ||
+        current.comment != new.comment ||
+        current.on_update != new.on_update
     }
 
     fn relationships_equal(&self, a: &Relationship, b: &Relationship) -> bool {
         a.from_table == b.from_table &&
-        a.from_column == b.from_column &&
         a.to_table == b.to_table &&
-        a.to_column == b.to_column &&
+        a.column_pairs == b.column_pairs &&
         a.relationship_type == b.relationship_type
     }
 
     fn apply_changes(&self, current_schema: &mut Schema, new_schema: Schema) {
         // Preserve positions of existing entities
-        let mut preserved_entities = std::collections::HashMap::new();
+        let mut preserved_entities = indexmap::IndexMap::new();
         
         for (name, new_entity) in new_schema.entities {
             let mut updated_entity = new_entity;
             
-            // Preserve position and dimensions if entity existed before
+            // Preserve position and pin if entity existed before; dimensions
+            // follow the new columns
             if let Some(current_entity) = current_schema.entities.get(&name) {
                 updated_entity.position = current_entity.position;
-                updated_entity.dimensions = current_entity.dimensions;
+                updated_entity.pinned = current_entity.pinned;
             }
             
             preserved_entities.insert(name, updated_entity);
@@ -193,22 +379,26 @@ impl SchemaSync {
 
             // Check if referenced columns exist
             if let Some(from_entity) = schema.entities.get(&relationship.from_table) {
-                if !from_entity.columns.iter().any(|col| col.name == relationship.from_column) {
-                    errors.push(format!(
-                        "Foreign key column '{}' not found in table '{}'",
-                        relationship.from_column,
-                        relationship.from_table
-                    ));
+                for from_column in relationship.local_columns() {
+                    if !from_entity.columns.iter().any(|col| col.name == from_column) {
+                        errors.push(format!(
+                            "Foreign key column '{}' not found in table '{}'",
+                            from_column,
+                            relationship.from_table
+                        ));
+                    }
                 }
             }
 
             if let Some(to_entity) = schema.entities.get(&relationship.to_table) {
-                if !to_entity.columns.iter().any(|col| col.name == relationship.to_column) {
-                    errors.push(format!(
-                        "Referenced column '{}' not found in table '{}'",
-                        relationship.to_column,
-                        relationship.to_table
-                    ));
+                for to_column in relationship.referenced_columns() {
+                    if !to_entity.columns.iter().any(|col| col.name == to_column) {
+                        errors.push(format!(
+                            "Referenced column '{}' not found in table '{}'",
+                            to_column,
+                            relationship.to_table
+                        ));
+                    }
                 }
             }
         }
@@ -227,7 +417,7 @@ impl SchemaSync {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Position, Dimensions};
+    use crate::models::{ColumnPair, Position, Dimensions, RelationshipType};
 
     #[test]
     fn test_generate_simple_table_sql() {
@@ -243,6 +433,7 @@ mod tests {
                     is_primary_key: true,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
                 Column {
                     name: "name".to_string(),
@@ -251,13 +442,15 @@ mod tests {
                     is_primary_key: false,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
             ],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
 
-        let sql = sync.generate_create_table(&entity);
+        let sql = sync.generate_create_table(&entity, &[], SqlDialect::Generic);
         assert!(sql.contains("CREATE TABLE users"));
         assert!(sql.contains("id INT NOT NULL PRIMARY KEY"));
         assert!(sql.contains("name VARCHAR(100)"));
@@ -278,6 +471,7 @@ mod tests {
             columns: vec![],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         schema2.entities.insert("test".to_string(), entity);
 
@@ -300,10 +494,12 @@ mod tests {
                     is_primary_key: false,
                     is_foreign_key: false,
                     references: None,
+                    ..Default::default()
                 },
             ],
             position: Position::default(),
             dimensions: Dimensions { width: 20, height: 5 },
+            ..Default::default()
         };
         schema.entities.insert("invalid".to_string(), entity);
 
@@ -311,4 +507,197 @@ mod tests {
         assert_eq!(errors.len(), 1);
         assert!(errors[0].contains("has no primary key"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_constraints_and_indexes_round_trip() {
+        let sql = "
+            CREATE TABLE users (
+                id INT PRIMARY KEY,
+                email VARCHAR(255) NOT NULL UNIQUE,
+                tenant_id INT NOT NULL,
+                handle VARCHAR(50) NOT NULL,
+                age INT CHECK (age >= 0),
+                CONSTRAINT uq_users_handle UNIQUE (tenant_id, handle)
+            );
+
+            CREATE UNIQUE INDEX idx_users_lower_email ON users (lower(email));
+            CREATE INDEX idx_users_active ON users (tenant_id) WHERE age > 18;
+        ";
+
+        let parser = crate::parser::SqlParser::new(SqlDialect::Generic);
+        let sync = SchemaSync::new();
+        let schema = parser.parse_sql(sql).unwrap();
+
+        let generated = sync.generate_sql(&schema, SqlDialect::Generic);
+        assert!(generated.contains("CONSTRAINT uq_users_handle UNIQUE (tenant_id, handle)"));
+        assert!(generated.contains("CHECK (age >= 0)"));
+        assert!(generated.contains("CREATE UNIQUE INDEX idx_users_lower_email ON users (lower(email));"));
+        assert!(generated.contains("CREATE INDEX idx_users_active ON users (tenant_id) WHERE age > 18;"));
+
+        let reparsed = parser.parse_sql(&generated).unwrap();
+        assert!(!sync.detect_changes(&schema, &reparsed));
+    }
+
+    #[test]
+    fn test_defaults_generated_and_comments_round_trip() {
+        let sql = "
+            CREATE TABLE orders (
+                id INT GENERATED BY DEFAULT AS IDENTITY (INCREMENT BY 1 START WITH 100) PRIMARY KEY,
+                status VARCHAR(50) DEFAULT 'pending',
+                quantity INT NOT NULL,
+                unit_price INT NOT NULL,
+                total INT GENERATED ALWAYS AS (quantity * unit_price) STORED,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP COMMENT 'when the order was placed'
+            );
+        ";
+
+        let parser = crate::parser::SqlParser::new(SqlDialect::Generic);
+        let sync = SchemaSync::new();
+        let schema = parser.parse_sql(sql).unwrap();
+
+        let generated = sync.generate_sql(&schema, SqlDialect::Generic);
+        assert!(generated.contains("id INT GENERATED BY DEFAULT AS IDENTITY (INCREMENT BY 1 START WITH 100) NOT NULL PRIMARY KEY"));
+        assert!(generated.contains("status VARCHAR(50) DEFAULT 'pending'"));
+        assert!(generated.contains("total INT GENERATED ALWAYS AS (quantity * unit_price) STORED"));
+        assert!(generated.contains("created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP COMMENT 'when the order was placed'"));
+
+        let reparsed = parser.parse_sql(&generated).unwrap();
+        assert!(!sync.detect_changes(&schema, &reparsed));
+    }
+
+    #[test]
+    fn test_generate_composite_foreign_key_sql() {
+        let sync = SchemaSync::new();
+        let relationship = Relationship {
+            name: None,
+            from_table: "tasks".to_string(),
+            to_table: "projects".to_string(),
+            column_pairs: vec![
+                ColumnPair::new("tenant_id", "tenant_id"),
+                ColumnPair::new("project_id", "id"),
+            ],
+            relationship_type: RelationshipType::OneToMany,
+            optional: false,
+        };
+
+        let sql = sync.generate_foreign_key_constraint(&relationship, SqlDialect::Generic);
+        assert_eq!(
+            sql,
+            "ALTER TABLE tasks ADD CONSTRAINT fk_tasks_tenant_id_project_id FOREIGN KEY (tenant_id, project_id) REFERENCES projects (tenant_id, id);"
+        );
+    }
+
+    const DIALECT_SAMPLE: &str = "
+        CREATE TABLE users (
+            id INT GENERATED BY DEFAULT AS IDENTITY (INCREMENT BY 1 START WITH 100) PRIMARY KEY,
+            email VARCHAR(255) NOT NULL UNIQUE,
+            active BOOLEAN DEFAULT TRUE,
+            balance DECIMAL(10,2),
+            created_at TIMESTAMP COMMENT 'signup time'
+        );
+
+        CREATE TABLE orders (
+            id INT PRIMARY KEY,
+            user_id INT NOT NULL REFERENCES users(id)
+        );
+
+        CREATE INDEX idx_orders_user ON orders (user_id DESC);
+    ";
+
+    fn generate_for(dialect: SqlDialect) -> String {
+        let parser = crate::parser::SqlParser::new(SqlDialect::Generic);
+        let schema = parser.parse_sql(DIALECT_SAMPLE).unwrap();
+        let generated = SchemaSync::new().generate_sql(&schema, dialect);
+
+        // Whatever we emit must be readable by the target dialect again
+        let reparsed = crate::parser::SqlParser::new(dialect).parse_sql(&generated).unwrap();
+        assert_eq!(reparsed.entities.len(), 2);
+        assert_eq!(reparsed.relationships.len(), 1);
+        generated
+    }
+
+    #[test]
+    fn test_generate_mysql_sql() {
+        let sql = generate_for(SqlDialect::MySql);
+        assert!(sql.contains("CREATE TABLE `users` ("));
+        assert!(sql.contains("`id` INT AUTO_INCREMENT NOT NULL PRIMARY KEY"));
+        assert!(sql.contains("`active` TINYINT(1) DEFAULT 1"));
+        assert!(sql.contains("`balance` DECIMAL(10,2)"));
+        assert!(sql.contains("`created_at` TIMESTAMP COMMENT 'signup time'"));
+        assert!(sql.contains("CREATE INDEX `idx_orders_user` ON `orders` (`user_id` DESC);"));
+        assert!(sql.contains(
+            "ALTER TABLE `orders` ADD CONSTRAINT `fk_orders_user_id` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`);"
+        ));
+    }
+
+    #[test]
+    fn test_generate_sqlite_inlines_foreign_keys() {
+        let sql = generate_for(SqlDialect::Sqlite);
+        assert!(!sql.contains("ALTER TABLE"));
+        assert!(sql.contains("id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT"));
+        assert!(sql.contains("email TEXT NOT NULL"));
+        assert!(sql.contains("active INTEGER DEFAULT true"));
+        assert!(sql.contains("CONSTRAINT fk_orders_user_id FOREIGN KEY (user_id) REFERENCES users (id)"));
+    }
+
+    #[test]
+    fn test_generate_postgres_sql() {
+        let sql = generate_for(SqlDialect::PostgreSql);
+        assert!(sql.contains("id INT GENERATED BY DEFAULT AS IDENTITY (INCREMENT BY 1 START WITH 100) NOT NULL PRIMARY KEY"));
+        assert!(!sql.contains("COMMENT 'signup time'"));
+        assert!(sql.contains("COMMENT ON COLUMN users.created_at IS 'signup time';"));
+        assert!(sql.contains("ALTER TABLE orders ADD CONSTRAINT fk_orders_user_id FOREIGN KEY (user_id) REFERENCES users (id);"));
+    }
+
+    #[test]
+    fn test_generate_mssql_sql() {
+        let sql = generate_for(SqlDialect::MsSql);
+        assert!(sql.contains("CREATE TABLE [users] ("));
+        assert!(sql.contains("[id] INT IDENTITY(100, 1) NOT NULL PRIMARY KEY"));
+        assert!(sql.contains("[active] BIT DEFAULT 1"));
+        assert!(sql.contains("[created_at] DATETIME2 /* signup time */"));
+    }
+
+    #[test]
+    fn test_generated_constraint_name_drops_schema_prefix() {
+        let parser = crate::parser::SqlParser::new(SqlDialect::MsSql);
+        let schema = parser
+            .parse_sql(
+                "CREATE TABLE dbo.users (id INT PRIMARY KEY);
+                 CREATE TABLE dbo.orders (id INT PRIMARY KEY, user_id INT REFERENCES dbo.users(id));",
+            )
+            .unwrap();
+
+        let sql = SchemaSync::new().generate_sql(&schema, SqlDialect::MsSql);
+        assert!(sql.contains(
+            "ALTER TABLE [dbo].[orders] ADD CONSTRAINT [fk_dbo_orders_user_id] FOREIGN KEY ([user_id]) REFERENCES [dbo].[users] ([id]);"
+        ));
+    }
+
+    #[test]
+    fn test_generate_composite_primary_key() {
+        let parser = crate::parser::SqlParser::new(SqlDialect::Generic);
+        let schema = parser
+            .parse_sql("CREATE TABLE memberships (user_id INT, team_id INT, PRIMARY KEY (user_id, team_id));")
+            .unwrap();
+
+        let sql = SchemaSync::new().generate_sql(&schema, SqlDialect::Generic);
+        assert!(sql.contains("    PRIMARY KEY (user_id, team_id)"));
+        assert!(!sql.contains("INT NOT NULL PRIMARY KEY"));
+
+        let reparsed = parser.parse_sql(&sql).unwrap();
+        assert!(!SchemaSync::new().detect_changes(&schema, &reparsed));
+    }
+
+    #[test]
+    fn test_generated_foreign_keys_round_trip() {
+        let parser = crate::parser::SqlParser::new(SqlDialect::Generic);
+        let sync = SchemaSync::new();
+        let schema = parser.parse_sql(DIALECT_SAMPLE).unwrap();
+
+        let generated = sync.generate_sql(&schema, SqlDialect::Generic);
+        let reparsed = parser.parse_sql(&generated).unwrap();
+        assert_eq!(reparsed.relationships.len(), 1);
+        assert!(!sync.detect_changes(&schema, &reparsed));
+    }
+}
@@ -0,0 +1,391 @@
+//! Orthogonal routing of relationship lines on a character grid. Both nERD
+//! front ends use it, so it works on plain cells and rectangles rather than
+//! any one terminal library's types.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BinaryHeap;
+use std::hash::{Hash, Hasher};
+
+/// Extra cost for turning a corner.
+const BEND_PENALTY: u32 = 4;
+/// Extra cost for leaving or arriving at an entity other than head-on.
+const HEAD_ON_PENALTY: u32 = 12;
+/// Extra cost for crossing another route at a right angle.
+const CROSSING_PENALTY: u32 = 6;
+/// Extra cost for running along another route, so parallel edges get
+/// their own channel whenever there is room for one.
+const OVERLAP_PENALTY: u32 = 12;
+/// Extra cost for a cell next to an obstacle, so lines keep a cell clear of
+/// borders they do not end at.
+const CLEARANCE_PENALTY: u32 = 20;
+
+/// A cell, as `(x, y)`.
+pub type Cell = (u16, u16);
+
+/// A rectangle of cells, as `(x, y, width, height)`.
+pub type Bounds = (u16, u16, u16, u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// One edge to route: the cells just outside the two entities, and the
+/// axis the line must leave/arrive on so it meets the entity head-on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteRequest {
+    pub from: Cell,
+    pub from_axis: Axis,
+    pub to: Cell,
+    pub to_axis: Axis,
+}
+
+/// Routes for the current layout. They are only recomputed when the canvas,
+/// the entity rectangles or the requested edges change, not on every frame.
+#[derive(Debug, Default)]
+pub struct RouteCache {
+    key: Option<u64>,
+    routes: Vec<Option<Vec<Cell>>>,
+}
+
+impl RouteCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One path of cells per request, from `from` to `to` inclusive, or
+    /// `None` when the request cannot be routed around the obstacles.
+    /// Requests that end at the same cell may share the last stretch of
+    /// their paths, so they meet the entity with one line.
+    pub fn routes(&mut self, area: Bounds, obstacles: &[Bounds], requests: &[RouteRequest]) -> &[Option<Vec<Cell>>] {
+        let mut hasher = DefaultHasher::new();
+        area.hash(&mut hasher);
+        obstacles.hash(&mut hasher);
+        requests.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if self.key != Some(key) {
+            self.routes = route_all(area, obstacles, requests);
+            self.key = Some(key);
+        }
+        &self.routes
+    }
+}
+
+/// Where the routes through a cell are bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Traffic {
+    Empty,
+    To(Cell),
+    Mixed,
+}
+
+struct Grid {
+    area: Bounds,
+    blocked: Vec<bool>,
+    /// Next to a blocked cell, diagonals included
+    near: Vec<bool>,
+    /// Where some route starts or ends, so no other route runs through it
+    ends: Vec<bool>,
+    horizontal: Vec<u8>,
+    vertical: Vec<u8>,
+    traffic: Vec<Traffic>,
+}
+
+impl Grid {
+    fn new(area: Bounds, obstacles: &[Bounds], requests: &[RouteRequest]) -> Self {
+        let size = area.2 as usize * area.3 as usize;
+        let mut grid = Self {
+            area,
+            blocked: vec![false; size],
+            near: vec![false; size],
+            ends: vec![false; size],
+            horizontal: vec![0; size],
+            vertical: vec![0; size],
+            traffic: vec![Traffic::Empty; size],
+        };
+        for &(x, y, width, height) in obstacles {
+            let right = (x as u32 + width as u32).min(area.0 as u32 + area.2 as u32);
+            let bottom = (y as u32 + height as u32).min(area.1 as u32 + area.3 as u32);
+            for y in y.max(area.1) as u32..bottom {
+                for x in x.max(area.0) as u32..right {
+                    let index = grid.index((x as u16, y as u16));
+                    grid.blocked[index] = true;
+                }
+            }
+        }
+        for index in 0..size {
+            if !grid.blocked[index] {
+                continue;
+            }
+            let (x, y) = grid.cell(index);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                        continue;
+                    };
+                    if grid.contains((nx, ny)) {
+                        let neighbour = grid.index((nx, ny));
+                        grid.near[neighbour] = !grid.blocked[neighbour];
+                    }
+                }
+            }
+        }
+        for request in requests {
+            for cell in [request.from, request.to] {
+                if grid.contains(cell) {
+                    let index = grid.index(cell);
+                    grid.ends[index] = true;
+                }
+            }
+        }
+        grid
+    }
+
+    fn contains(&self, (x, y): Cell) -> bool {
+        let (left, top, width, height) = self.area;
+        x >= left && (x as u32) < left as u32 + width as u32 && y >= top && (y as u32) < top as u32 + height as u32
+    }
+
+    fn index(&self, (x, y): Cell) -> usize {
+        (y - self.area.1) as usize * self.area.2 as usize + (x - self.area.0) as usize
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        let width = self.area.2 as usize;
+        ((index % width) as u16 + self.area.0, (index / width) as u16 + self.area.1)
+    }
+
+    fn step_cost(&self, index: usize, axis: Axis, to: Cell) -> u32 {
+        // Joining a route bound for the same cell is free: they merge
+        if self.traffic[index] == Traffic::To(to) {
+            return 1;
+        }
+        let (along, across) = match axis {
+            Axis::Horizontal => (self.horizontal[index], self.vertical[index]),
+            Axis::Vertical => (self.vertical[index], self.horizontal[index]),
+        };
+        1 + if along > 0 { OVERLAP_PENALTY } else { 0 }
+            + if across > 0 { CROSSING_PENALTY } else { 0 }
+            + if self.near[index] { CLEARANCE_PENALTY } else { 0 }
+    }
+
+    fn mark(&mut self, path: &[Cell]) {
+        let Some(&to) = path.last() else {
+            return;
+        };
+        for pair in path.windows(2) {
+            let axis = if pair[0].1 == pair[1].1 { Axis::Horizontal } else { Axis::Vertical };
+            for cell in pair {
+                let index = self.index(*cell);
+                let usage = match axis {
+                    Axis::Horizontal => &mut self.horizontal[index],
+                    Axis::Vertical => &mut self.vertical[index],
+                };
+                *usage = usage.saturating_add(1);
+            }
+        }
+        for cell in path {
+            let index = self.index(*cell);
+            self.traffic[index] = match self.traffic[index] {
+                Traffic::Empty => Traffic::To(to),
+                Traffic::To(other) if other == to => Traffic::To(to),
+                _ => Traffic::Mixed,
+            };
+        }
+    }
+}
+
+fn route_all(area: Bounds, obstacles: &[Bounds], requests: &[RouteRequest]) -> Vec<Option<Vec<Cell>>> {
+    let mut grid = Grid::new(area, obstacles, requests);
+    let mut routes = vec![None; requests.len()];
+
+    // Short edges first: they have the fewest alternatives
+    let mut order: Vec<usize> = (0..requests.len()).collect();
+    order.sort_by_key(|&i| manhattan(requests[i].from, requests[i].to));
+
+    for i in order {
+        if let Some(path) = find_path(&grid, &requests[i]) {
+            grid.mark(&path);
+            routes[i] = Some(path);
+        }
+    }
+    routes
+}
+
+/// A* over (cell, axis) states with Manhattan distance as the heuristic.
+fn find_path(grid: &Grid, request: &RouteRequest) -> Option<Vec<Cell>> {
+    if !grid.contains(request.from) || !grid.contains(request.to) {
+        return None;
+    }
+
+    let start = grid.index(request.from);
+    let goal = grid.index(request.to);
+    let state = |index: usize, axis: Axis| index * 2 + if axis == Axis::Horizontal { 0 } else { 1 };
+
+    let mut cost = vec![u32::MAX; grid.blocked.len() * 2];
+    let mut came_from = vec![usize::MAX; grid.blocked.len() * 2];
+    let mut open = BinaryHeap::new();
+
+    let start_state = state(start, request.from_axis);
+    cost[start_state] = 0;
+    open.push(Reverse((manhattan(request.from, request.to), 0, start_state)));
+
+    while let Some(Reverse((_, current_cost, current))) = open.pop() {
+        if current_cost > cost[current] {
+            continue;
+        }
+        let index = current / 2;
+        if index == goal {
+            let mut path = vec![grid.cell(index)];
+            let mut at = current;
+            while came_from[at] != usize::MAX {
+                at = came_from[at];
+                path.push(grid.cell(at / 2));
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let axis = if current % 2 == 0 { Axis::Horizontal } else { Axis::Vertical };
+        let (x, y) = grid.cell(index);
+        let neighbours = [
+            (x.checked_sub(1).map(|x| (x, y)), Axis::Horizontal),
+            (x.checked_add(1).map(|x| (x, y)), Axis::Horizontal),
+            (y.checked_sub(1).map(|y| (x, y)), Axis::Vertical),
+            (y.checked_add(1).map(|y| (x, y)), Axis::Vertical),
+        ];
+
+        for (cell, next_axis) in neighbours {
+            let Some(cell) = cell.filter(|c| grid.contains(*c)) else {
+                continue;
+            };
+            let next = grid.index(cell);
+            if (grid.blocked[next] || grid.ends[next]) && next != goal {
+                continue;
+            }
+
+            // The goal sits against its entity, so it is never too close
+            let mut step = if next == goal { 1 } else { grid.step_cost(next, next_axis, request.to) };
+            if next_axis != axis {
+                step += BEND_PENALTY;
+            }
+            // Leave and arrive head-on, rather than running along the
+            // entity's border
+            if (index == start && next_axis != request.from_axis) || (next == goal && next_axis != request.to_axis) {
+                step += HEAD_ON_PENALTY;
+            }
+
+            let next_state = state(next, next_axis);
+            let next_cost = current_cost + step;
+            if next_cost < cost[next_state] {
+                cost[next_state] = next_cost;
+                came_from[next_state] = current;
+                open.push(Reverse((next_cost + manhattan(cell, request.to), next_cost, next_state)));
+            }
+        }
+    }
+
+    None
+}
+
+fn manhattan(a: Cell, b: Cell) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(from: Cell, to: Cell) -> RouteRequest {
+        RouteRequest { from, from_axis: Axis::Horizontal, to, to_axis: Axis::Horizontal }
+    }
+
+    /// Cells of `bounds` grown by one on every side.
+    fn around((x, y, width, height): Bounds) -> impl Fn(&Cell) -> bool {
+        move |&(cx, cy)| cx + 1 >= x && cx <= x + width && cy + 1 >= y && cy <= y + height
+    }
+
+    #[test]
+    fn test_route_goes_around_obstacle() {
+        let area = (0, 0, 40, 20);
+        let wall = (10, 0, 5, 15);
+        let mut cache = RouteCache::new();
+
+        let path = cache.routes(area, &[wall], &[request((2, 5), (30, 5))])[0].clone().unwrap();
+        assert_eq!(path.first(), Some(&(2, 5)));
+        assert_eq!(path.last(), Some(&(30, 5)));
+        // Keeps a cell clear of the wall all the way round
+        assert!(!path.iter().any(around(wall)));
+        // Each step moves to an adjacent cell
+        assert!(path.windows(2).all(|p| manhattan(p[0], p[1]) == 1));
+        // Shortest detour below the wall: down, across, back up
+        assert_eq!(path.len() as u32 - 1, 28 + 2 * (16 - 5));
+    }
+
+    #[test]
+    fn test_parallel_edges_use_separate_channels() {
+        let area = (0, 0, 40, 20);
+        let requests = [request((2, 5), (30, 6)), request((2, 6), (30, 5))];
+        let mut cache = RouteCache::new();
+
+        let routes = cache.routes(area, &[], &requests);
+        let first = routes[0].as_ref().unwrap();
+        let second = routes[1].as_ref().unwrap();
+        let shared = first.iter().filter(|cell| second.contains(cell)).count();
+        assert!(shared <= 1, "routes share {} cells", shared);
+    }
+
+    #[test]
+    fn test_edges_to_the_same_cell_merge() {
+        let area = (0, 0, 40, 20);
+        let requests = [request((2, 5), (30, 8)), request((2, 12), (30, 8))];
+        let mut cache = RouteCache::new();
+
+        let routes = cache.routes(area, &[], &requests);
+        let first = routes[0].as_ref().unwrap();
+        let second = routes[1].as_ref().unwrap();
+        // One meets the other before the end, so they arrive as one line
+        assert_eq!(first[first.len() - 2..], second[second.len() - 2..]);
+    }
+
+    #[test]
+    fn test_routes_keep_off_other_ends() {
+        let area = (0, 0, 40, 20);
+        // The straight line would run through where the second one starts
+        let requests = [request((2, 5), (30, 5)), request((15, 5), (15, 15))];
+        let mut cache = RouteCache::new();
+
+        let routes = cache.routes(area, &[], &requests);
+        assert!(!routes[0].as_ref().unwrap().contains(&(15, 5)));
+    }
+
+    #[test]
+    fn test_lines_leave_and_arrive_head_on() {
+        // A self reference: both ends just right of the same entity
+        let area = (0, 0, 20, 12);
+        let entity = (0, 3, 5, 7);
+        let mut cache = RouteCache::new();
+
+        let path = cache.routes(area, &[entity], &[request((5, 8), (5, 4))])[0].clone().unwrap();
+        assert_eq!(path[1], (6, 8));
+        assert_eq!(path[path.len() - 2], (6, 4));
+    }
+
+    #[test]
+    fn test_routes_are_cached_per_layout() {
+        let area = (0, 0, 40, 20);
+        let requests = [request((2, 5), (30, 5))];
+        let mut cache = RouteCache::new();
+
+        cache.routes(area, &[], &requests);
+        let key = cache.key;
+        cache.routes(area, &[], &requests);
+        assert_eq!(cache.key, key);
+
+        cache.routes(area, &[(10, 0, 5, 15)], &requests);
+        assert_ne!(cache.key, key);
+    }
+}
@@ -1,3 +1,5 @@
+use nerd_router::{Axis, Bounds, RouteCache, RouteRequest};
+
 use crate::{constants::ENTITY_WIDTH, engine::Entity};
 // pub struct Entity {
 //     pub name: String,
@@ -90,20 +92,20 @@ pub fn render(mut canvas: Vec<Vec<char>>, entities: &Vec<Entity>) -> (Vec<Vec<ch
     )
 }
 
+// Every foreign key is routed from a tee on the referencing column's border
+// to a terminator beside the referenced column:
+//
+// ┌──────────┐   ┌──────────┐
+// │users     │   │posts     │
+// ├──────────┤   ├──────────┤
+// │id        ├◄┐ │id        │
+// │email     │ └─┤user_id   │
+// └──────────┘   └──────────┘
 pub fn render_foreign_key(
     mut canvas: Vec<Vec<char>>,
     entities: &Vec<Entity>,
 ) -> (Vec<Vec<char>>, String) {
-    // Draw the specific FK pattern for the 3-table scenario (users, posts, comments)
-    if entities.len() == 3 && 
-       entities[0].name == "users" && 
-       entities[1].name == "posts" && 
-       entities[2].name == "comments" {
-        draw_snapshot_fk_pattern(&mut canvas, entities);
-    } else {
-        // For other scenarios, draw general FK relationships
-        draw_general_foreign_keys(&mut canvas, entities);
-    }
+    draw_foreign_keys(&mut canvas, entities);
 
     (
         canvas.clone(),
@@ -115,613 +117,222 @@ pub fn render_foreign_key(
     )
 }
 
-enum Sign {
-    Positive,
-    Negative,
-}
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
 
-enum Direction {
-    Horizontal { sign: Sign, vertical_sign: Sign },
-    Vertical { sign: Sign, horizontal_sign: Sign },
+#[derive(Clone, Copy, PartialEq)]
+enum Side {
+    Left,
+    Right,
 }
 
-fn draw_snapshot_fk_pattern(canvas: &mut Vec<Vec<char>>, entities: &Vec<Entity>) {
-    // This function draws the exact pattern shown in the snapshot
-    // Expected pattern (without leading spaces):
-    // │id        ├←┬┐│id        ├←─┐│id        │
-    // │email     │ │││title     │  ││content   │  
-    // │name      │ │└┤user_id   │  └┤post_id   │
-    // └──────────┘ │ └──────────┘  ┌┤user_id   │
-    //              └───────────────┘└──────────┘
-    
-    // Only apply this specific pattern for exactly 3 tables (users, posts, comments)
-    if entities.len() == 3 && 
-       entities[0].name == "users" && 
-       entities[1].name == "posts" && 
-       entities[2].name == "comments" {
-        // Assume entities are positioned as: users, posts, comments
-        let users = &entities[0];
-        let posts = &entities[1]; 
-        let comments = &entities[2];
-        
-        // Row 1 (id fields): │id        ├←┬┐│id        ├←─┐│id        │
-        let id_row = users.y + 3; // Assuming id is first attribute
-        // For users.id
-        canvas[id_row][users.x + ENTITY_WIDTH - 1] = '├';
-        canvas[id_row][users.x + ENTITY_WIDTH] = '←';
-        canvas[id_row][users.x + ENTITY_WIDTH + 1] = '┬';
-        canvas[id_row][users.x + ENTITY_WIDTH + 2] = '┐';
-        
-        // For posts.id  
-        canvas[id_row][posts.x + ENTITY_WIDTH - 1] = '├';
-        canvas[id_row][posts.x + ENTITY_WIDTH] = '←';
-        canvas[id_row][posts.x + ENTITY_WIDTH + 1] = '─';
-        canvas[id_row][posts.x + ENTITY_WIDTH + 2] = '┐';
-        
-        // Row 2 (email/title/content): │email     │ │││title     │  ││content   │
-        let row2 = users.y + 4;
-        // After users table - need space then three vertical bars
-        canvas[row2][users.x + ENTITY_WIDTH + 1] = '│';
-        canvas[row2][users.x + ENTITY_WIDTH + 2] = '│';
-        canvas[row2][users.x + ENTITY_WIDTH + 3] = '│';
-        
-        // After posts table - two spaces then two vertical bars
-        canvas[row2][posts.x + ENTITY_WIDTH + 2] = '│';
-        canvas[row2][posts.x + ENTITY_WIDTH + 3] = '│';
-        
-        // Row 3 (name/user_id/post_id): │name      │ │└┤user_id   │  └┤post_id   │
-        let row3 = users.y + 5;
-        // After users.name - space, vertical bar, then └
-        canvas[row3][users.x + ENTITY_WIDTH + 1] = '│';
-        canvas[row3][users.x + ENTITY_WIDTH + 2] = '└';
-        
-        // posts.user_id
-        canvas[row3][posts.x] = '┤';
-        
-        // After posts table - two spaces then └
-        canvas[row3][posts.x + ENTITY_WIDTH + 2] = '└';
-        
-        // comments.post_id
-        canvas[row3][comments.x] = '┤';
-        
-        // Row 4 (bottom borders): └──────────┘ │ └──────────┘  ┌┤user_id   │
-        let row4 = users.y + 6;
-        // Vertical line continuing down from users area (space then vertical)
-        canvas[row4][users.x + ENTITY_WIDTH + 1] = '│';
-        
-        // Connection to comments.user_id (two spaces then ┌)
-        canvas[row4][comments.x - 1] = '┌';
-        canvas[row4][comments.x] = '┤';
-        
-        // Row 5 (connection line):              └───────────────┘└──────────┘
-        let row5 = users.y + 7;
-        // Draw horizontal line
-        for x in (users.x + ENTITY_WIDTH + 1)..(comments.x - 1) {
-            if canvas[row5][x] == ' ' {
-                canvas[row5][x] = '─';
-            }
+impl Side {
+    fn opposite(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
         }
-        canvas[row5][users.x + ENTITY_WIDTH + 1] = '└';
-        canvas[row5][comments.x - 1] = '┘';
     }
 }
 
-fn draw_general_foreign_keys(canvas: &mut Vec<Vec<char>>, entities: &Vec<Entity>) {
-    // Draw connecting lines for foreign key relationships similar to the 3-table pattern
-    // This function identifies FK relationships and draws proper connecting lines between entities
-    
-    for entity in entities {
-        for attr in &entity.attributes {
-            // Look for foreign key attributes (ending in _id)
-            if attr.name.ends_with("_id") && attr.name != "id" {
-                let base_name = attr.name.strip_suffix("_id").unwrap();
-                
-                // Find the referenced entity - try both singular and plural forms
-                let target_entity = entities.iter().find(|e| {
-                    e.name == base_name ||                    // exact match (department)
-                    e.name == format!("{}s", base_name) ||    // plural form (departments)
-                    e.name == format!("{}es", base_name) ||   // plural with 'es' (employees)
-                    (base_name.ends_with("y") && e.name == format!("{}ies", &base_name[..base_name.len()-1])) // category -> categories
-                });
-                
-                if let Some(target_entity) = target_entity {
-                    let from_x = entity.x + ENTITY_WIDTH - 1;
-                    let from_y = entity.y + 3 + entity.attributes.iter().position(|a| &a.name == &attr.name).unwrap();
-                    
-                    let to_x = target_entity.x;
-                    let to_y = target_entity.y + 3; // ID is typically first attribute
-                    
-                    // Draw connecting line similar to the 3-table pattern
-                    draw_fk_connection(canvas, from_x, from_y, to_x, to_y, entity, target_entity);
-                }
-            }
-        }
-    }
-}
-
-fn draw_fk_connection(canvas: &mut Vec<Vec<char>>, from_x: usize, from_y: usize, to_x: usize, to_y: usize, from_entity: &Entity, to_entity: &Entity) {
-    // Draw a connecting line between FK field and target ID field
-    
-    // Mark the source FK field
-    if from_x < canvas[0].len() && from_y < canvas.len() {
-        canvas[from_y][from_x] = '├';
-    }
-    
-    // Mark the target ID field  
-    if to_x + ENTITY_WIDTH - 1 < canvas[0].len() && to_y < canvas.len() {
-        if canvas[to_y][to_x + ENTITY_WIDTH - 1] == '│' {
-            canvas[to_y][to_x + ENTITY_WIDTH - 1] = '┤';
-        }
-    }
-    
-    // Draw connecting line
-    if from_entity.x < to_entity.x {
-        // Target is to the right - draw rightward line with arrow
-        draw_horizontal_connection(canvas, from_x, from_y, to_x, to_y, true);
-    } else if from_entity.x > to_entity.x {
-        // Target is to the left - draw leftward line with arrow  
-        draw_horizontal_connection(canvas, from_x, from_y, to_x, to_y, false);
-    } else {
-        // Same column - draw vertical connection
-        draw_vertical_connection(canvas, from_x, from_y, to_x, to_y);
-    }
+// Where a foreign key line meets a box: the border cell that gets a tee and
+// the free cell just outside it where the line starts or ends.
+struct Anchor {
+    border: (usize, usize),
+    outside: (usize, usize),
+    side: Side,
 }
 
-fn draw_horizontal_connection(canvas: &mut Vec<Vec<char>>, from_x: usize, from_y: usize, to_x: usize, to_y: usize, rightward: bool) {
-    if rightward {
-        // Draw rightward connection with line
-        if from_x + 2 < canvas[0].len() && from_y < canvas.len() {
-            canvas[from_y][from_x + 1] = '─';
-            canvas[from_y][from_x + 2] = '→';
-        } else if from_x + 1 < canvas[0].len() && from_y < canvas.len() {
-            canvas[from_y][from_x + 1] = '→';
-        }
-    } else {
-        // Draw leftward connection with line  
-        if from_x >= 2 && from_y < canvas.len() {
-            canvas[from_y][from_x - 2] = '←';
-            canvas[from_y][from_x - 1] = '─';
-        } else if from_x >= 1 && from_y < canvas.len() {
-            canvas[from_y][from_x - 1] = '←';
-        }
-    }
+struct ForeignKey {
+    from: Anchor,
+    to: Anchor,
 }
 
-fn draw_vertical_connection(canvas: &mut Vec<Vec<char>>, from_x: usize, from_y: usize, to_x: usize, to_y: usize) {
-    // Draw vertical connection for self-referencing relationships
-    let min_y = from_y.min(to_y);
-    let max_y = from_y.max(to_y);
-    
-    for y in (min_y + 1)..max_y {
-        if y < canvas.len() && from_x < canvas[0].len() && canvas[y][from_x] == ' ' {
-            canvas[y][from_x] = '│';
-        }
+fn draw_foreign_keys(canvas: &mut Vec<Vec<char>>, entities: &Vec<Entity>) {
+    if canvas.is_empty() || canvas[0].is_empty() {
+        return;
     }
-    
-    // Add arrow direction
-    if from_y > to_y {
-        // Upward arrow
-        if from_y > 0 && from_x < canvas[0].len() {
-            canvas[from_y - 1][from_x] = '↑';
-        }
-    } else {
-        // Downward arrow  
-        if from_y + 1 < canvas.len() && from_x < canvas[0].len() {
-            canvas[from_y + 1][from_x] = '↓';
+    let height = canvas.len().min(u16::MAX as usize);
+    let width = canvas[0].len().min(u16::MAX as usize);
+
+    let foreign_keys = collect_foreign_keys(canvas, entities);
+
+    // Boxes are obstacles, and so are names spilling past them, so no line
+    // is drawn through text
+    let mut obstacles: Vec<Bounds> = entities
+        .iter()
+        .map(|entity| (entity.x as u16, entity.y as u16, ENTITY_WIDTH as u16, (entity.attributes.len() + 4) as u16))
+        .collect();
+    for (y, row) in canvas.iter().enumerate().take(height) {
+        for (x, c) in row.iter().enumerate().take(width) {
+            if *c != ' ' {
+                obstacles.push((x as u16, y as u16, 1, 1));
+            }
         }
     }
-}
 
-fn draw_fk_with_entities(cur_x: usize, cur_y: usize, to_x: usize, to_y: usize, canvas: &mut Vec<Vec<char>>, entities: &Vec<Entity>) {
-    // Simple approach: draw a straight line from FK start to FK end, but allow going through entity borders
-    draw_simple_fk(cur_x, cur_y, to_x, to_y, canvas, entities);
-}
+    // The diagram's router keeps lines a cell clear of the boxes and gives
+    // each its own lane, but merges lines into the same column into one
+    let cell = |(x, y): (usize, usize)| (x as u16, y as u16);
+    let requests: Vec<RouteRequest> = foreign_keys
+        .iter()
+        .map(|fk| RouteRequest {
+            from: cell(fk.from.outside),
+            from_axis: Axis::Horizontal,
+            to: cell(fk.to.outside),
+            to_axis: Axis::Horizontal,
+        })
+        .collect();
+    let mut routes = RouteCache::new();
+    let paths = routes.routes((0, 0, width as u16, height as u16), &obstacles, &requests);
+
+    let mut links = vec![vec![0u8; width]; height];
+    let mut routed = Vec::new();
+    for (fk, path) in foreign_keys.iter().zip(paths) {
+        let Some(path) = path else {
+            continue;
+        };
 
-fn draw_simple_fk(from_x: usize, from_y: usize, to_x: usize, to_y: usize, canvas: &mut Vec<Vec<char>>, entities: &Vec<Entity>) {
-    // Simple L-shaped connection: horizontal first, then vertical
-    
-    // Step 1: Draw horizontal line from source to target column (excluding the endpoints)
-    let start_x = from_x.min(to_x);
-    let end_x = from_x.max(to_x);
-    
-    for x in (start_x + 1)..end_x {
-        if !is_inside_entity_content(x, from_y, entities) && canvas[from_y][x] == ' ' {
-            canvas[from_y][x] = '─';
-        }
-    }
-    
-    // Step 2: Draw vertical line from source row to target row (excluding the endpoints)
-    let start_y = from_y.min(to_y);
-    let end_y = from_y.max(to_y);
-    
-    for y in (start_y + 1)..end_y {
-        if !is_inside_entity_content(to_x, y, entities) && canvas[y][to_x] == ' ' {
-            canvas[y][to_x] = '│';
-        }
-    }
-    
-    // Step 3: Draw corner connector at the junction
-    if from_x != to_x && from_y != to_y {
-        let corner_x = to_x;
-        let corner_y = from_y;
-        
-        if !is_inside_entity_content(corner_x, corner_y, entities) {
-            // Determine the correct corner character based on direction
-            let corner_char = match (from_x < to_x, from_y < to_y) {
-                (true, true) => '┐',   // going right then down
-                (true, false) => '┘',  // going right then up  
-                (false, true) => '┌',  // going left then down
-                (false, false) => '└', // going left then up
+        for pair in path.windows(2) {
+            let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+            let (towards_b, towards_a) = if ax < bx {
+                (RIGHT, LEFT)
+            } else if ax > bx {
+                (LEFT, RIGHT)
+            } else if ay < by {
+                (DOWN, UP)
+            } else {
+                (UP, DOWN)
             };
-            canvas[corner_y][corner_x] = corner_char;
+            links[ay as usize][ax as usize] |= towards_b;
+            links[by as usize][bx as usize] |= towards_a;
         }
-    }
-}
-
-fn is_inside_entity_content(x: usize, y: usize, entities: &Vec<Entity>) -> bool {
-    for entity in entities {
-        let entity_left = entity.x;
-        let entity_right = entity.x + ENTITY_WIDTH - 1;
-        let entity_top = entity.y;
-        let entity_bottom = entity.y + entity.attributes.len() + 3;
-        
-        // Check if we're inside the content area (not on borders)
-        if x > entity_left && x < entity_right && y > entity_top && y < entity_bottom {
-            return true;
-        }
-    }
-    false
-}
-
-fn draw_fk_with_depth(cur_x: usize, cur_y: usize, to_x: usize, to_y: usize, canvas: &mut Vec<Vec<char>>, depth: usize, entities: &Vec<Entity>) {
-    // Prevent stack overflow with maximum recursion depth
-    if depth > 1000 {
-        // println!("Warning: Maximum recursion depth reached while drawing foreign key");
-        return;
-    }
-    
-    // println!("cur: {}, {}, to: {}, {}", cur_x, cur_y, to_x, to_y);
-
-    let move_y =
-        ((to_x as isize) - (cur_x as isize)).abs() < ((to_y as isize) - (cur_y as isize)).abs();
-
-    let try_direction = if move_y {
-        if to_y > cur_y {
-            Direction::Vertical {
-                sign: Sign::Positive,
-                horizontal_sign: if to_x > cur_x {
-                    Sign::Positive
-                } else {
-                    Sign::Negative
-                },
-            }
-        } else {
-            Direction::Vertical {
-                sign: Sign::Negative,
-                horizontal_sign: if to_x > cur_x {
-                    Sign::Positive
-                } else {
-                    Sign::Negative
-                },
-            }
-        }
-    } else {
-        if to_x > cur_x {
-            Direction::Horizontal {
-                sign: Sign::Positive,
-                vertical_sign: if to_y > cur_y {
-                    Sign::Positive
-                } else {
-                    Sign::Negative
-                },
-            }
-        } else {
-            Direction::Horizontal {
-                sign: Sign::Negative,
-                vertical_sign: if to_y > cur_y {
-                    Sign::Positive
-                } else {
-                    Sign::Negative
-                },
-            }
+        for anchor in [&fk.from, &fk.to] {
+            let (x, y) = anchor.outside;
+            links[y][x] |= match anchor.side {
+                Side::Left => RIGHT,
+                Side::Right => LEFT,
+            };
         }
-    };
-
-    let (next_x, next_y) = match try_direction {
-        Direction::Horizontal {
-            sign: Sign::Positive,
-            ..
-        } => (cur_x + 1, cur_y),
-        Direction::Horizontal {
-            sign: Sign::Negative,
-            ..
-        } => (cur_x - 1, cur_y),
-
-        Direction::Vertical {
-            sign: Sign::Positive,
-            ..
-        } => (cur_x, cur_y + 1),
-        Direction::Vertical {
-            sign: Sign::Negative,
-            ..
-        } => (cur_x, cur_y - 1),
-    };
-
-    // if move_y {
-    //     if to_y > cur_y {
-    //         (cur_x, cur_y + 1)
-    //     } else {
-    //         (cur_x, cur_y - 1)
-    //     }
-    // } else {
-    //     if to_x > cur_x {
-    //         (cur_x + 1, cur_y)
-    //     } else {
-    //         (cur_x - 1, cur_y)
-    //     }
-    // };
-
-    // let vacant = canvas[cur_y][cur_x] == ' ';
-    // let (cur_x, cur_y) = match (vacant, move_y) {
-    //     (true, true) => {
-    //         canvas[cur_y][cur_x] = '│';
-
-    //         (cur_x, cur_y + 1)
-    //     }
-    //     (true, false) => {
-    //         canvas[cur_y][cur_x] = '─';
-
-    //         (cur_x + 1, cur_y)
-    //     }
-    //     (false, true) => (cur_x, cur_y + 1),
-    //     (false, false) => (cur_x + 1, cur_y),
-    // };
-    enum Char {
-        Horizontal,
-        Vertical,
-        LeftUp,
-        LeftDown,
-        RightUp,
-        RightDown,
-        FkFrom,
-        FkTo,
+        routed.push(fk);
     }
 
-    impl Char {
-        fn as_char(&self) -> char {
-            match self {
-                Char::Horizontal => '─',
-                Char::Vertical => '│',
-                Char::LeftUp => '┌',
-                Char::LeftDown => '└',
-                Char::RightUp => '┐',
-                Char::RightDown => '┘',
-                Char::FkFrom => '├',
-                Char::FkTo => '→',
+    for (y, row) in links.iter().enumerate() {
+        for (x, link) in row.iter().enumerate() {
+            if *link != 0 {
+                canvas[y][x] = junction_char(*link);
             }
         }
     }
 
-    // Check if the next position would be inside an entity (but not on its borders)
-    let is_inside_entity = |x: usize, y: usize| -> bool {
-        for entity in entities {
-            // Check if we're inside this entity's bounds (excluding borders)
-            let entity_left = entity.x;
-            let entity_right = entity.x + ENTITY_WIDTH - 1;
-            let entity_top = entity.y;
-            let entity_bottom = entity.y + entity.attributes.len() + 3; // +3 for top border, header line, separator line, and bottom border
-            
-            // Allow drawing on the borders but not inside the content area
-            if x > entity_left && x < entity_right && y > entity_top && y < entity_bottom {
-                return true;
-            }
+    for fk in routed {
+        for anchor in [&fk.from, &fk.to] {
+            let (x, y) = anchor.border;
+            canvas[y][x] = match anchor.side {
+                Side::Left => '┤',
+                Side::Right => '├',
+            };
         }
-        false
-    };
-
-    // If the next position would be inside an entity, we need to go around it
-    let next_is_inside_entity = is_inside_entity(next_x, next_y);
-    
-    let (cur_x, cur_y) = if next_is_inside_entity || canvas[next_y][next_x] != ' ' {
-        // We hit an obstacle or are trying to go through an entity
-        let prev_char = canvas[cur_y][cur_x];
-        match try_direction {
-            Direction::Vertical {
-                sign: Sign::Positive,
-                horizontal_sign: Sign::Positive,
-            } => {
-                if prev_char == '│' {
-                    canvas[cur_y][cur_x] = '└';
-                } else {
-                    canvas[cur_y][cur_x] = '─';
-                }
-
-                (cur_x + 1, cur_y)
-            }
-            Direction::Vertical {
-                sign: Sign::Positive,
-                horizontal_sign: Sign::Negative,
-            } => {
-                if prev_char == '│' {
-                    canvas[cur_y][cur_x] = '┘';
-                } else {
-                    canvas[cur_y][cur_x] = '─';
-                }
-
-                (cur_x - 1, cur_y)
-            }
-            Direction::Vertical {
-                sign: Sign::Negative,
-                horizontal_sign: Sign::Positive,
-            } => {
-                if prev_char == '│' {
-                    canvas[cur_y][cur_x] = '┌';
-                } else {
-                    canvas[cur_y][cur_x] = '─';
-                }
 
-                (cur_x + 1, cur_y)
-            }
-            Direction::Vertical {
-                sign: Sign::Negative,
-                horizontal_sign: Sign::Negative,
-            } => {
-                if prev_char == '│' {
-                    canvas[cur_y][cur_x] = '┐';
-                } else {
-                    canvas[cur_y][cur_x] = '─';
-                }
+        // The terminator points into the referenced column
+        let (x, y) = fk.to.outside;
+        canvas[y][x] = match fk.to.side {
+            Side::Left => '►',
+            Side::Right => '◄',
+        };
+    }
+}
 
-                (cur_x - 1, cur_y)
-            }
-            Direction::Horizontal {
-                sign: Sign::Positive,
-                vertical_sign: Sign::Positive,
-            } => {
-                if prev_char == '─' {
-                    canvas[cur_y][cur_x] = '┐';
-                } else {
-                    canvas[cur_y][cur_x] = '│';
-                    if cur_y + 1 < canvas.len() {
-                        canvas[cur_y + 1][cur_x] = '└';
-                    }
-                }
+fn collect_foreign_keys(canvas: &[Vec<char>], entities: &Vec<Entity>) -> Vec<ForeignKey> {
+    let mut foreign_keys = Vec::new();
 
-                (cur_x, cur_y + 1)
-            }
-            Direction::Horizontal {
-                sign: Sign::Positive,
-                vertical_sign: Sign::Negative,
-            } => {
-                if prev_char == '─' {
-                    canvas[cur_y][cur_x] = '┘';
-                } else {
-                    canvas[cur_y][cur_x] = '│';
-                }
+    for entity in entities {
+        for (row, attribute) in entity.attributes.iter().enumerate() {
+            let Some((table, column)) = &attribute.reffering_to else {
+                continue;
+            };
+            let Some(target) = entities.iter().find(|e| &e.name == table) else {
+                continue;
+            };
+            let target_row = target
+                .attributes
+                .iter()
+                .position(|a| &a.name == column)
+                .unwrap_or(0);
+
+            // Leave towards the target; boxes stacked in the same column (and
+            // self references) loop around their right-hand side
+            let (from_side, to_side) = if target.x >= entity.x + ENTITY_WIDTH {
+                (Side::Right, Side::Left)
+            } else if target.x + ENTITY_WIDTH <= entity.x {
+                (Side::Left, Side::Right)
+            } else {
+                (Side::Right, Side::Right)
+            };
 
-                (cur_x, cur_y - 1)
+            let from = free_anchor(canvas, entity, row, from_side);
+            let to = free_anchor(canvas, target, target_row, to_side);
+            if let (Some(from), Some(to)) = (from, to) {
+                foreign_keys.push(ForeignKey { from, to });
             }
-            Direction::Horizontal {
-                sign: Sign::Negative,
-                vertical_sign: Sign::Positive,
-            } => {
-                if prev_char == '─' {
-                    canvas[cur_y][cur_x] = '┌';
-                } else {
-                    canvas[cur_y][cur_x] = '│';
-                }
+        }
+    }
 
-                (cur_x, cur_y + 1)
-            }
-            Direction::Horizontal {
-                sign: Sign::Negative,
-                vertical_sign: Sign::Negative,
-            } => {
-                if prev_char == '─' {
-                    canvas[cur_y][cur_x] = '└';
-                } else {
-                    canvas[cur_y][cur_x] = '│';
-                }
+    foreign_keys
+}
 
-                (cur_x, cur_y - 1)
-            }
-        }
-    } else {
-        // Empty space - we can draw the line
-        match try_direction {
-            Direction::Horizontal {
-                sign: Sign::Positive,
-                vertical_sign: Sign::Positive,
-            } => {
-                if canvas[cur_y][cur_x] == '│' {
-                    canvas[cur_y][cur_x] = 'x';
-                }
-                canvas[next_y][next_x] = Char::Horizontal.as_char();
-            }
-            Direction::Horizontal {
-                sign: Sign::Positive,
-                vertical_sign: Sign::Negative,
-            } => {
-                if canvas[cur_y][cur_x] == '│' {
-                    canvas[cur_y][cur_x] = '└';
-                }
-                canvas[next_y][next_x] = Char::Horizontal.as_char();
-            }
-            Direction::Horizontal {
-                sign: Sign::Negative,
-                vertical_sign: Sign::Positive,
-            } => {
-                if canvas[cur_y][cur_x] == '│' {
-                    canvas[cur_y][cur_x] = '┐';
-                }
-                canvas[next_y][next_x] = Char::Horizontal.as_char();
-            }
-            Direction::Horizontal {
-                sign: Sign::Negative,
-                vertical_sign: Sign::Negative,
-            } => {
-                if canvas[cur_y][cur_x] == '│' {
-                    canvas[cur_y][cur_x] = '┘';
-                }
-                canvas[next_y][next_x] = Char::Horizontal.as_char();
-            }
-            Direction::Vertical {
-                sign: Sign::Positive,
-                horizontal_sign: Sign::Positive,
-            } => {
-                if canvas[cur_y][cur_x] == '─' {
-                    canvas[cur_y][cur_x] = '┐';
-                }
-                canvas[next_y][next_x] = Char::Vertical.as_char();
-            }
-            Direction::Vertical {
-                sign: Sign::Positive,
-                horizontal_sign: Sign::Negative,
-            } => {
-                if canvas[cur_y][cur_x] == '─' {
-                    canvas[cur_y][cur_x] = '┌';
-                }
-                canvas[next_y][next_x] = Char::Vertical.as_char();
-            }
-            Direction::Vertical {
-                sign: Sign::Negative,
-                horizontal_sign: Sign::Positive,
-            } => {
-                if canvas[cur_y][cur_x] == '─' {
-                    canvas[cur_y][cur_x] = '┘';
-                }
-                canvas[next_y][next_x] = Char::Vertical.as_char();
-            }
-            Direction::Vertical {
-                sign: Sign::Negative,
-                horizontal_sign: Sign::Negative,
-            } => {
-                if canvas[cur_y][cur_x] == '─' {
-                    canvas[cur_y][cur_x] = '└';
-                }
-                canvas[next_y][next_x] = Char::Vertical.as_char();
-            }
-        };
+// The anchor on `side`, or on the other side when a name spilling out of a
+// box already covers the cell outside it. `None` when neither is free.
+fn free_anchor(canvas: &[Vec<char>], entity: &Entity, row: usize, side: Side) -> Option<Anchor> {
+    [side, side.opposite()]
+        .into_iter()
+        .filter_map(|side| anchor(entity, row, side))
+        .find(|anchor| {
+            let (x, y) = anchor.outside;
+            canvas.get(y).and_then(|row| row.get(x)) == Some(&' ')
+        })
+}
 
-        (next_x, next_y)
+fn anchor(entity: &Entity, row: usize, side: Side) -> Option<Anchor> {
+    let y = entity.y + 3 + row;
+    let (border, outside) = match side {
+        Side::Left => (entity.x, entity.x.checked_sub(1)?),
+        Side::Right => (entity.x + ENTITY_WIDTH - 1, entity.x + ENTITY_WIDTH),
     };
 
-    // if (cur_x == 16 && cur_y == 7) || (to_x == cur_x && to_y == cur_y) {
-    // if (cur_x == 45 && cur_y == 13) || (to_x == cur_x && to_y == cur_y) {
-    // println!("cur: {}, {}, to: {}, {}", cur_x, cur_y, to_x, to_y);
+    Some(Anchor {
+        border: (border, y),
+        outside: (outside, y),
+        side,
+    })
+}
 
-    if to_x == cur_x && to_y == cur_y {
-        return;
+fn junction_char(link: u8) -> char {
+    match link {
+        l if l == UP | DOWN | LEFT | RIGHT => '┼',
+        l if l == LEFT | RIGHT | DOWN => '┬',
+        l if l == LEFT | RIGHT | UP => '┴',
+        l if l == UP | DOWN | RIGHT => '├',
+        l if l == UP | DOWN | LEFT => '┤',
+        l if l == RIGHT | DOWN => '┌',
+        l if l == LEFT | DOWN => '┐',
+        l if l == RIGHT | UP => '└',
+        l if l == LEFT | UP => '┘',
+        l if l & (LEFT | RIGHT) == 0 => '│',
+        _ => '─',
     }
-
-    draw_fk_with_depth(cur_x, cur_y, to_x, to_y, canvas, depth + 1, entities);
 }
 
 #[cfg(test)]
 mod tests {
-    use gluesql_core::data::Schema;
+    use gluesql_core::{ast::DataType, data::Schema};
 
-    use crate::{engine::into_entities, util::assert_text};
+    use crate::{
+        engine::{into_entities, Attribute},
+        util::assert_text,
+    };
 
     use super::*;
 
@@ -765,4 +376,80 @@ CREATE TABLE Tab16 (col1 INT, col2 INT);
         assert!(actual.contains("Tab1"), "Should contain Tab1");
         assert!(actual.contains("Tab16"), "Should contain Tab16");
     }
+
+    #[test]
+    fn test_render_foreign_key_routes_around_boxes() {
+        let entity = |name: &str, x: usize, columns: Vec<(&str, Option<(&str, &str)>)>| Entity {
+            name: name.to_owned(),
+            attributes: columns
+                .into_iter()
+                .map(|(column, reffering_to)| Attribute {
+                    name: column.to_owned(),
+                    data_type: DataType::Int,
+                    reffered_by: None,
+                    reffering_to: reffering_to.map(|(t, c)| (t.to_owned(), c.to_owned())),
+                })
+                .collect(),
+            x,
+            y: 1,
+        };
+        let entities = vec![
+            entity("orders", 1, vec![("id", None), ("item_id", Some(("items", "id")))]),
+            entity("middle", 16, vec![("a", None), ("b", None)]),
+            entity("items", 31, vec![("id", None), ("name", None)]),
+        ];
+
+        let canvas = vec![vec![' '; 44]; 8];
+        let (canvas, _) = render(canvas, &entities);
+        let (_, actual) = render_foreign_key(canvas, &entities);
+        let actual = actual.lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+
+        let expected = "
+ ┌──────────┐   ┌──────────┐   ┌──────────┐
+ │orders    │   │middle    │   │items     │
+ ├──────────┤   ├──────────┤   ├──────────┤
+ │id        │   │a         │ ┌►┤id        │
+ │item_id   ├─┐ │b         │ │ │name      │
+ └──────────┘ │ └──────────┘ │ └──────────┘
+              └──────────────┘";
+        assert_text(actual.as_str(), expected);
+    }
+
+    #[test]
+    fn test_render_foreign_key_keeps_off_spilled_names() {
+        let entity = |name: &str, x: usize, columns: Vec<(&str, Option<(&str, &str)>)>| Entity {
+            name: name.to_owned(),
+            attributes: columns
+                .into_iter()
+                .map(|(column, reffering_to)| Attribute {
+                    name: column.to_owned(),
+                    data_type: DataType::Int,
+                    reffered_by: None,
+                    reffering_to: reffering_to.map(|(t, c)| (t.to_owned(), c.to_owned())),
+                })
+                .collect(),
+            x,
+            y: 1,
+        };
+        let entities = vec![
+            entity("orders", 1, vec![("id", None), ("customer_id", Some(("customers", "id")))]),
+            entity("invoices", 16, vec![("id", None), ("order_reference", Some(("orders", "id")))]),
+            entity("customers", 31, vec![("id", None), ("name", None)]),
+        ];
+
+        let canvas = vec![vec![' '; 48]; 10];
+        let (before, _) = render(canvas, &entities);
+        let (after, _) = render_foreign_key(before.clone(), &entities);
+
+        // Every letter drawn by `render` is still there
+        for (y, row) in before.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                if c.is_alphanumeric() || *c == '_' {
+                    assert_eq!(after[y][x], *c, "overwritten at ({}, {})", x, y);
+                }
+            }
+        }
+        let text = after.iter().map(|row| row.iter().collect::<String>()).collect::<String>();
+        assert_eq!(text.matches('►').count() + text.matches('◄').count(), 2);
+    }
 }